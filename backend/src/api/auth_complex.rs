@@ -1,12 +1,16 @@
 use actix_web::{web, HttpResponse, Result, Scope};
 use sqlx::PgPool;
-use crate::models::{CreateUserRequest, LoginRequest, LoginResponse, UserResponse};
-use crate::auth::AuthService;
+use crate::config::Config;
+use crate::models::{CreateUserRequest, LoginRequest, RefreshTokenRequest, UserResponse};
+use crate::api::errors::database_timeout;
+use crate::auth::{AuthService, AuthenticatedUser};
+use crate::db;
 
 pub fn configure() -> Scope {
     web::scope("/auth")
         .route("/register", web::post().to(register))
         .route("/login", web::post().to(login))
+        .route("/refresh", web::post().to(refresh_token))
         .route("/me", web::get().to(get_current_user))
 }
 
@@ -19,11 +23,16 @@ async fn register(
             let response: UserResponse = user.into();
             Ok(HttpResponse::Created().json(response))
         }
+        // Emails are unique case-insensitively (idx_users_email_lower)
+        Err(e) if db::is_unique_violation(&e) => Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Email already registered",
+            "message": "An account with this email address already exists"
+        }))),
+        Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
         Err(e) => {
             tracing::error!("Registration failed: {}", e);
-            Ok(HttpResponse::BadRequest().json(serde_json::json!({
-                "error": "Registration failed",
-                "message": e.to_string()
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Registration failed"
             })))
         }
     }
@@ -31,10 +40,12 @@ async fn register(
 
 async fn login(
     pool: web::Data<PgPool>,
+    config: web::Data<Config>,
     req: web::Json<LoginRequest>,
 ) -> Result<HttpResponse> {
-    match AuthService::login(&pool, req.into_inner()).await {
+    match AuthService::login(&pool, &config, req.into_inner()).await {
         Ok(response) => Ok(HttpResponse::Ok().json(response)),
+        Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
         Err(e) => {
             tracing::error!("Login failed: {}", e);
             Ok(HttpResponse::Unauthorized().json(serde_json::json!({
@@ -45,9 +56,47 @@ async fn login(
     }
 }
 
-async fn get_current_user() -> Result<HttpResponse> {
-    // TODO: Implement JWT token validation and user retrieval
-    Ok(HttpResponse::Ok().json(serde_json::json!({
-        "message": "JWT authentication not yet implemented"
-    })))
+async fn refresh_token(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    req: web::Json<RefreshTokenRequest>,
+) -> Result<HttpResponse> {
+    match AuthService::refresh(&pool, &config, &req.refresh_token).await {
+        Ok(Some(response)) => Ok(HttpResponse::Ok().json(response)),
+        Ok(None) => Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "Unauthorized",
+            "message": "Invalid refresh token"
+        }))),
+        Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
+        Err(e) => {
+            tracing::error!("Token refresh failed: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Token refresh failed"
+            })))
+        }
+    }
+}
+
+async fn get_current_user(
+    pool: web::Data<PgPool>,
+    user: AuthenticatedUser,
+) -> Result<HttpResponse> {
+    match AuthService::get_user(&pool, user.user_id).await {
+        Ok(Some(user)) => {
+            let response: UserResponse = user.into();
+            Ok(HttpResponse::Ok().json(response))
+        }
+        // A validly signed token for a deleted account
+        Ok(None) => Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "Unauthorized",
+            "message": "User no longer exists"
+        }))),
+        Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
+        Err(e) => {
+            tracing::error!("Failed to load current user: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to retrieve user"
+            })))
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,139 @@
+use actix_web::error::{InternalError, JsonPayloadError, QueryPayloadError};
+use actix_web::{web, HttpRequest, HttpResponse};
+
+// Response for statements cancelled by the server-side statement_timeout.
+// Only the database-backed handlers call this.
+#[allow(dead_code)]
+pub fn database_timeout() -> HttpResponse {
+    HttpResponse::ServiceUnavailable()
+        .insert_header(("Retry-After", "5"))
+        .json(serde_json::json!({
+            "error": "Database timeout",
+            "message": "The request took too long to process, please retry"
+        }))
+}
+
+// Body extraction failures get the same `{"error", "message"}` shape as
+// handler errors instead of actix's plain-text default
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default().error_handler(json_error_handler)
+}
+
+fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let (mut response, message) = match &err {
+        JsonPayloadError::ContentType => (
+            HttpResponse::UnsupportedMediaType(),
+            "Expected a JSON body with Content-Type: application/json".to_string(),
+        ),
+        JsonPayloadError::Overflow { limit } | JsonPayloadError::OverflowKnownLength { limit, .. } => (
+            HttpResponse::PayloadTooLarge(),
+            format!("Request body exceeds the {} byte limit", limit),
+        ),
+        JsonPayloadError::Deserialize(e) => (
+            HttpResponse::BadRequest(),
+            format!("Invalid JSON body: {}", e),
+        ),
+        other => (HttpResponse::BadRequest(), other.to_string()),
+    };
+
+    let response = response.json(serde_json::json!({
+        "error": "Invalid request body",
+        "message": message
+    }));
+    InternalError::from_response(err, response).into()
+}
+
+// Same treatment for query strings, e.g. an unknown sort or non-numeric limit
+pub fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default().error_handler(query_error_handler)
+}
+
+fn query_error_handler(err: QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let message = match &err {
+        QueryPayloadError::Deserialize(e) => format!("Invalid query string: {}", e),
+        other => other.to_string(),
+    };
+
+    let response = HttpResponse::BadRequest().json(serde_json::json!({
+        "error": "Invalid query parameters",
+        "message": message
+    }));
+    InternalError::from_response(err, response).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::{header::CONTENT_TYPE, StatusCode};
+    use actix_web::{test, App};
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Page {
+        limit: i64,
+    }
+
+    #[derive(Deserialize)]
+    struct Payload {
+        name: String,
+    }
+
+    async fn echo(payload: web::Json<Payload>) -> HttpResponse {
+        HttpResponse::Ok().json(serde_json::json!({ "name": payload.name }))
+    }
+
+    async fn page(query: web::Query<Page>) -> HttpResponse {
+        HttpResponse::Ok().json(serde_json::json!({ "limit": query.limit }))
+    }
+
+    async fn post(content_type: &str, body: &'static str) -> (StatusCode, serde_json::Value) {
+        let app = test::init_service(
+            App::new()
+                .app_data(json_config())
+                .route("/echo", web::post().to(echo)),
+        )
+        .await;
+        let req = test::TestRequest::post()
+            .uri("/echo")
+            .insert_header((CONTENT_TYPE, content_type))
+            .set_payload(body)
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        (res.status(), test::read_body_json(res).await)
+    }
+
+    #[actix_web::test]
+    async fn non_json_content_type_is_rejected_with_json_415() {
+        let (status, body) = post("text/plain", "name=maxamem").await;
+
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(body["error"], "Invalid request body");
+        assert!(body["message"].as_str().is_some_and(|message| message.contains("application/json")));
+    }
+
+    #[actix_web::test]
+    async fn malformed_json_is_rejected_with_json_400() {
+        let (status, body) = post("application/json", r#"{"name": 42}"#).await;
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "Invalid request body");
+        assert!(body["message"].is_string());
+    }
+
+    #[actix_web::test]
+    async fn invalid_query_is_rejected_with_json_400() {
+        let app = test::init_service(
+            App::new()
+                .app_data(query_config())
+                .route("/page", web::get().to(page)),
+        )
+        .await;
+        let req = test::TestRequest::get().uri("/page?limit=abc").to_request();
+        let res = test::call_service(&app, req).await;
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["error"], "Invalid query parameters");
+        assert!(body["message"].as_str().is_some_and(|message| message.starts_with("Invalid query string")));
+    }
+}
\ No newline at end of file
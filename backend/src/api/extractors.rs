@@ -0,0 +1,61 @@
+use actix_web::{dev::Payload, error::InternalError, FromRequest, HttpRequest, HttpResponse};
+use std::future::{ready, Ready};
+use uuid::Uuid;
+
+// The `{id}` path segment parsed as a Uuid. Malformed ids are rejected with a
+// 400 in the standard error shape rather than actix's plain-text 404.
+pub struct ResourceId(pub Uuid);
+
+impl FromRequest for ResourceId {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let result = match req.match_info().get("id") {
+            Some(raw) => Uuid::parse_str(raw).map(ResourceId).map_err(|_| invalid_resource_id(raw)),
+            None => Err(actix_web::error::ErrorInternalServerError("Route has no {id} segment")),
+        };
+        ready(result)
+    }
+}
+
+fn invalid_resource_id(raw: &str) -> actix_web::Error {
+    let message = format!("'{}' is not a valid UUID", raw);
+    let response = HttpResponse::BadRequest().json(serde_json::json!({
+        "error": "Invalid resource id",
+        "message": message
+    }));
+    InternalError::from_response(message, response).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, web, App};
+
+    async fn show(ResourceId(id): ResourceId) -> HttpResponse {
+        HttpResponse::Ok().json(serde_json::json!({ "id": id }))
+    }
+
+    #[actix_web::test]
+    async fn malformed_id_is_rejected_with_json_400() {
+        let app = test::init_service(App::new().route("/projects/{id}", web::get().to(show))).await;
+        let req = test::TestRequest::get().uri("/projects/not-a-uuid").to_request();
+        let res = test::call_service(&app, req).await;
+
+        assert_eq!(res.status(), actix_web::http::StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["error"], "Invalid resource id");
+        assert!(body["message"].as_str().is_some_and(|message| message.contains("not-a-uuid")));
+    }
+
+    #[actix_web::test]
+    async fn valid_id_is_extracted() {
+        let id = Uuid::new_v4();
+        let app = test::init_service(App::new().route("/projects/{id}", web::get().to(show))).await;
+        let req = test::TestRequest::get().uri(&format!("/projects/{}", id)).to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        assert_eq!(body["id"], id.to_string());
+    }
+}
\ No newline at end of file
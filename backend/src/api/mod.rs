@@ -1,2 +1,4 @@
 pub mod auth;
+pub mod errors;
+pub mod extractors;
 pub mod projects;
\ No newline at end of file
@@ -1,4 +1,5 @@
 use actix_web::{web, HttpResponse, Result, Scope};
+use crate::api::extractors::ResourceId;
 
 pub fn configure() -> Scope {
     web::scope("/projects")
@@ -15,17 +16,24 @@ async fn create_project() -> Result<HttpResponse> {
 }
 
 async fn list_projects() -> Result<HttpResponse> {
-    Ok(HttpResponse::Ok().json(serde_json::json!([])))
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "projects": [],
+        "total": 0,
+        "limit": 20,
+        "offset": 0
+    })))
 }
 
-async fn get_project() -> Result<HttpResponse> {
+async fn get_project(ResourceId(id): ResourceId) -> Result<HttpResponse> {
     Ok(HttpResponse::Ok().json(serde_json::json!({
+        "id": id,
         "message": "Get project endpoint - database not connected yet"
     })))
 }
 
-async fn delete_project() -> Result<HttpResponse> {
+async fn delete_project(ResourceId(id): ResourceId) -> Result<HttpResponse> {
     Ok(HttpResponse::Ok().json(serde_json::json!({
+        "id": id,
         "message": "Delete project endpoint - database not connected yet"
     })))
 }
\ No newline at end of file
@@ -1,28 +1,106 @@
 use actix_web::{web, HttpResponse, Result, Scope};
+use serde::Deserialize;
 use sqlx::PgPool;
-use crate::models::{CreateProjectRequest, ProjectResponse};
+use std::collections::BTreeMap;
+use crate::api::errors::database_timeout;
+use crate::api::extractors::ResourceId;
+use crate::auth::{AuthService, AuthenticatedUser};
+use crate::db;
+use crate::models::{ArtifactType, BulkDeleteRequest, BulkDeleteResponse, CreateProjectRequest, DeleteProjectQuery, GenerationArtifact, ProjectListQuery, ProjectListResponse, ProjectResponse, ProjectStatus, ProjectVisibility, TemplateListQuery, TemplateResponse, UpdateTemplateRequest, User};
+use crate::services::artifact::ArtifactService;
 use crate::services::project::ProjectService;
 
 pub fn configure() -> Scope {
     web::scope("/projects")
         .route("", web::post().to(create_project))
         .route("", web::get().to(list_projects))
+        .route("/bulk-delete", web::post().to(bulk_delete_projects))
         .route("/{id}", web::get().to(get_project))
         .route("/{id}", web::delete().to(delete_project))
+        .route("/{id}/template", web::put().to(update_template))
+        .route("/{id}/documents", web::get().to(get_documents))
+        .route("/{id}/export", web::get().to(export_project))
+        .route("/{id}/artifacts/{artifact_type}/versions", web::get().to(list_artifact_versions))
+        .route("/{id}/artifacts/{artifact_type}/diff", web::get().to(diff_artifact_versions))
+}
+
+pub fn configure_templates() -> Scope {
+    web::scope("/templates")
+        .route("", web::get().to(list_templates))
+        .route("/{id}/clone", web::post().to(clone_template))
+}
+
+const MAX_BULK_DELETE: usize = 100;
+const MAX_PAGE_SIZE: i64 = 100;
+
+// Error response when the project doesn't exist or belongs to someone else
+async fn check_project_access(
+    pool: &PgPool,
+    project_id: uuid::Uuid,
+    user_id: uuid::Uuid,
+) -> Option<HttpResponse> {
+    match ProjectService::get_project(pool, project_id, Some(user_id)).await {
+        Ok(Some(_)) => None,
+        Ok(None) => Some(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Project not found"
+        }))),
+        Err(e) if db::is_statement_timeout(&e) => Some(database_timeout()),
+        Err(e) => {
+            tracing::error!("Failed to get project: {}", e);
+            Some(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to retrieve project"
+            })))
+        }
+    }
+}
+
+// The caller's account, needed for their tier's quota; a deleted account
+// with a still-valid token is treated as unauthorized
+async fn load_account(pool: &PgPool, user_id: uuid::Uuid) -> Result<User, HttpResponse> {
+    match AuthService::get_user(pool, user_id).await {
+        Ok(Some(account)) => Ok(account),
+        Ok(None) => Err(HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "Unauthorized",
+            "message": "User no longer exists"
+        }))),
+        Err(e) if db::is_statement_timeout(&e) => Err(database_timeout()),
+        Err(e) => {
+            tracing::error!("Failed to load user for quota check: {}", e);
+            Err(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Project creation failed"
+            })))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ArtifactPath {
+    artifact_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiffQuery {
+    from: Option<i32>,
+    to: Option<i32>,
 }
 
 async fn create_project(
     pool: web::Data<PgPool>,
+    user: AuthenticatedUser,
     req: web::Json<CreateProjectRequest>,
 ) -> Result<HttpResponse> {
-    // TODO: Extract user_id from JWT token
-    let user_id = uuid::Uuid::new_v4(); // Placeholder
+    let account = match load_account(&pool, user.user_id).await {
+        Ok(account) => account,
+        Err(response) => return Ok(response),
+    };
     
-    match ProjectService::create_project(&pool, user_id, req.into_inner()).await {
-        Ok(project) => {
+    match ProjectService::create_project(&pool, &account, req.into_inner()).await {
+        Ok(Ok(project)) => {
             let response: ProjectResponse = project.into();
             Ok(HttpResponse::Created().json(response))
         }
+        Ok(Err(exceeded)) => Ok(HttpResponse::PaymentRequired().json(exceeded)),
+        Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
         Err(e) => {
             tracing::error!("Project creation failed: {}", e);
             Ok(HttpResponse::BadRequest().json(serde_json::json!({
@@ -33,13 +111,45 @@ async fn create_project(
     }
 }
 
-async fn list_projects(pool: web::Data<PgPool>) -> Result<HttpResponse> {
-    // TODO: Extract user_id from JWT token and filter by user
-    match ProjectService::list_projects(&pool, None).await {
-        Ok(projects) => {
-            let responses: Vec<ProjectResponse> = projects.into_iter().map(|p| p.into()).collect();
-            Ok(HttpResponse::Ok().json(responses))
+async fn list_projects(
+    pool: web::Data<PgPool>,
+    user: AuthenticatedUser,
+    query: web::Query<ProjectListQuery>,
+) -> Result<HttpResponse> {
+    if let (Some(after), Some(before)) = (query.created_after, query.created_before) {
+        if after > before {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid date range",
+                "message": "created_after must not be later than created_before"
+            })));
+        }
+    }
+    
+    if query.limit < 1 || query.limit > MAX_PAGE_SIZE || query.offset < 0 {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid pagination",
+            "message": format!("limit must be between 1 and {} and offset must not be negative", MAX_PAGE_SIZE)
+        })));
+    }
+    
+    let status = match query.status.as_deref().map(ProjectStatus::try_from).transpose() {
+        Ok(status) => status,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid status filter",
+                "message": e.to_string()
+            })));
         }
+    };
+    
+    match ProjectService::list_projects(&pool, Some(user.user_id), &query, status).await {
+        Ok((projects, total)) => Ok(HttpResponse::Ok().json(ProjectListResponse {
+            projects: projects.into_iter().map(|p| p.into()).collect(),
+            total,
+            limit: query.limit,
+            offset: query.offset,
+        })),
+        Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
         Err(e) => {
             tracing::error!("Failed to list projects: {}", e);
             Ok(HttpResponse::InternalServerError().json(serde_json::json!({
@@ -51,11 +161,12 @@ async fn list_projects(pool: web::Data<PgPool>) -> Result<HttpResponse> {
 
 async fn get_project(
     pool: web::Data<PgPool>,
-    path: web::Path<uuid::Uuid>,
+    user: AuthenticatedUser,
+    id: ResourceId,
 ) -> Result<HttpResponse> {
-    let project_id = path.into_inner();
+    let ResourceId(project_id) = id;
     
-    match ProjectService::get_project(&pool, project_id).await {
+    match ProjectService::get_project(&pool, project_id, Some(user.user_id)).await {
         Ok(Some(project)) => {
             let response: ProjectResponse = project.into();
             Ok(HttpResponse::Ok().json(response))
@@ -63,6 +174,7 @@ async fn get_project(
         Ok(None) => Ok(HttpResponse::NotFound().json(serde_json::json!({
             "error": "Project not found"
         }))),
+        Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
         Err(e) => {
             tracing::error!("Failed to get project: {}", e);
             Ok(HttpResponse::InternalServerError().json(serde_json::json!({
@@ -74,15 +186,18 @@ async fn get_project(
 
 async fn delete_project(
     pool: web::Data<PgPool>,
-    path: web::Path<uuid::Uuid>,
+    user: AuthenticatedUser,
+    id: ResourceId,
+    query: web::Query<DeleteProjectQuery>,
 ) -> Result<HttpResponse> {
-    let project_id = path.into_inner();
+    let ResourceId(project_id) = id;
     
-    match ProjectService::delete_project(&pool, project_id).await {
+    match ProjectService::delete_project(&pool, project_id, Some(user.user_id), query.hard).await {
         Ok(true) => Ok(HttpResponse::NoContent().finish()),
         Ok(false) => Ok(HttpResponse::NotFound().json(serde_json::json!({
             "error": "Project not found"
         }))),
+        Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
         Err(e) => {
             tracing::error!("Failed to delete project: {}", e);
             Ok(HttpResponse::InternalServerError().json(serde_json::json!({
@@ -90,4 +205,330 @@ async fn delete_project(
             })))
         }
     }
+}
+
+async fn update_template(
+    pool: web::Data<PgPool>,
+    user: AuthenticatedUser,
+    id: ResourceId,
+    req: web::Json<UpdateTemplateRequest>,
+) -> Result<HttpResponse> {
+    let ResourceId(project_id) = id;
+    
+    let visibility = match ProjectVisibility::try_from(req.visibility.as_str()) {
+        Ok(visibility) => visibility,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid visibility",
+                "message": e.to_string()
+            })));
+        }
+    };
+    
+    match ProjectService::update_template(&pool, project_id, user.user_id, req.is_template, visibility).await {
+        Ok(Some(project)) => {
+            let response: ProjectResponse = project.into();
+            Ok(HttpResponse::Ok().json(response))
+        }
+        // Public templates are visible to everyone, so tell non-owners why;
+        // anything else is reported as missing so private projects don't leak
+        Ok(None) => match ProjectService::get_project(&pool, project_id, None).await {
+            Ok(Some(project)) if project.is_template && matches!(project.visibility, ProjectVisibility::Public) => {
+                Ok(HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "Forbidden",
+                    "message": "Only the project owner can change its template settings"
+                })))
+            }
+            Ok(_) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Project not found"
+            }))),
+            Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
+            Err(e) => {
+                tracing::error!("Failed to get project: {}", e);
+                Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Failed to retrieve project"
+                })))
+            }
+        },
+        Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
+        Err(e) => {
+            tracing::error!("Failed to update template settings: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to update project"
+            })))
+        }
+    }
+}
+
+async fn list_templates(
+    pool: web::Data<PgPool>,
+    _user: AuthenticatedUser,
+    query: web::Query<TemplateListQuery>,
+) -> Result<HttpResponse> {
+    if query.limit < 1 || query.limit > MAX_PAGE_SIZE || query.offset < 0 {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid pagination",
+            "message": format!("limit must be between 1 and {} and offset must not be negative", MAX_PAGE_SIZE)
+        })));
+    }
+    
+    match ProjectService::list_public_templates(&pool, query.limit, query.offset).await {
+        Ok(templates) => {
+            let responses: Vec<TemplateResponse> = templates.into_iter().map(|p| p.into()).collect();
+            Ok(HttpResponse::Ok().json(responses))
+        }
+        Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
+        Err(e) => {
+            tracing::error!("Failed to list templates: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to retrieve templates"
+            })))
+        }
+    }
+}
+
+async fn clone_template(
+    pool: web::Data<PgPool>,
+    user: AuthenticatedUser,
+    id: ResourceId,
+) -> Result<HttpResponse> {
+    let ResourceId(template_id) = id;
+    
+    let account = match load_account(&pool, user.user_id).await {
+        Ok(account) => account,
+        Err(response) => return Ok(response),
+    };
+    
+    match ProjectService::clone_template(&pool, template_id, &account).await {
+        Ok(Some(Ok(project))) => {
+            let response: ProjectResponse = project.into();
+            Ok(HttpResponse::Created().json(response))
+        }
+        Ok(Some(Err(exceeded))) => Ok(HttpResponse::PaymentRequired().json(exceeded)),
+        Ok(None) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Template not found"
+        }))),
+        Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
+        Err(e) => {
+            tracing::error!("Failed to clone template: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to clone template"
+            })))
+        }
+    }
+}
+
+async fn bulk_delete_projects(
+    pool: web::Data<PgPool>,
+    user: AuthenticatedUser,
+    req: web::Json<BulkDeleteRequest>,
+) -> Result<HttpResponse> {
+    let BulkDeleteRequest { mut ids, hard } = req.into_inner();
+    ids.sort();
+    ids.dedup();
+    
+    if ids.is_empty() || ids.len() > MAX_BULK_DELETE {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Invalid bulk delete request",
+            "message": format!("Provide between 1 and {} project ids", MAX_BULK_DELETE)
+        })));
+    }
+    
+    match ProjectService::bulk_delete_projects(&pool, Some(user.user_id), &ids, hard).await {
+        Ok(deleted) => {
+            let skipped = ids.into_iter().filter(|id| !deleted.contains(id)).collect();
+            Ok(HttpResponse::Ok().json(BulkDeleteResponse {
+                deleted: deleted.len(),
+                skipped,
+            }))
+        }
+        Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
+        Err(e) => {
+            tracing::error!("Failed to bulk delete projects: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to delete projects"
+            })))
+        }
+    }
+}
+
+// Latest version of each generated document, keyed by artifact type
+async fn get_documents(
+    pool: web::Data<PgPool>,
+    user: AuthenticatedUser,
+    id: ResourceId,
+) -> Result<HttpResponse> {
+    let ResourceId(project_id) = id;
+    
+    if let Some(response) = check_project_access(&pool, project_id, user.user_id).await {
+        return Ok(response);
+    }
+    
+    match ArtifactService::latest_artifacts(&pool, project_id).await {
+        Ok(artifacts) => {
+            let documents: BTreeMap<&'static str, GenerationArtifact> = artifacts
+                .into_iter()
+                .map(|artifact| (artifact.artifact_type.as_str(), artifact))
+                .collect();
+            Ok(HttpResponse::Ok().json(documents))
+        }
+        Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
+        Err(e) => {
+            tracing::error!("Failed to get project documents: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to retrieve documents"
+            })))
+        }
+    }
+}
+
+async fn export_project(
+    pool: web::Data<PgPool>,
+    user: AuthenticatedUser,
+    id: ResourceId,
+) -> Result<HttpResponse> {
+    let ResourceId(project_id) = id;
+    
+    let project = match ProjectService::get_project(&pool, project_id, Some(user.user_id)).await {
+        Ok(Some(project)) => project,
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Project not found"
+            })));
+        }
+        Err(e) if db::is_statement_timeout(&e) => return Ok(database_timeout()),
+        Err(e) => {
+            tracing::error!("Failed to get project: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to retrieve project"
+            })));
+        }
+    };
+    
+    if !matches!(project.status, ProjectStatus::Complete) {
+        return Ok(HttpResponse::Conflict().json(serde_json::json!({
+            "error": "Project not complete",
+            "message": format!("Project is {}, documents can be exported once generation completes", project.status)
+        })));
+    }
+    
+    let archive = ArtifactService::latest_artifacts(&pool, project_id)
+        .await
+        .and_then(|artifacts| ArtifactService::export_zip(&artifacts));
+    
+    match archive {
+        Ok(bytes) => Ok(HttpResponse::Ok()
+            .content_type("application/zip")
+            .insert_header((
+                "Content-Disposition",
+                format!("attachment; filename=\"maxamem-{}.zip\"", project_id),
+            ))
+            .body(bytes)),
+        Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
+        Err(e) => {
+            tracing::error!("Failed to export project: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to export project"
+            })))
+        }
+    }
+}
+
+async fn list_artifact_versions(
+    pool: web::Data<PgPool>,
+    user: AuthenticatedUser,
+    id: ResourceId,
+    path: web::Path<ArtifactPath>,
+) -> Result<HttpResponse> {
+    let ResourceId(project_id) = id;
+    let artifact_type = path.into_inner().artifact_type;
+    
+    let artifact_type = match ArtifactType::try_from(artifact_type.as_str()) {
+        Ok(artifact_type) => artifact_type,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid artifact type",
+                "message": e.to_string()
+            })));
+        }
+    };
+    
+    if let Some(response) = check_project_access(&pool, project_id, user.user_id).await {
+        return Ok(response);
+    }
+    
+    match ArtifactService::list_versions(&pool, project_id, artifact_type).await {
+        Ok(versions) => Ok(HttpResponse::Ok().json(versions)),
+        Err(e) if db::is_statement_timeout(&e) => Ok(database_timeout()),
+        Err(e) => {
+            tracing::error!("Failed to list artifact versions: {}", e);
+            Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to retrieve artifact versions"
+            })))
+        }
+    }
+}
+
+async fn diff_artifact_versions(
+    pool: web::Data<PgPool>,
+    user: AuthenticatedUser,
+    id: ResourceId,
+    path: web::Path<ArtifactPath>,
+    query: web::Query<DiffQuery>,
+) -> Result<HttpResponse> {
+    let ResourceId(project_id) = id;
+    let artifact_type = path.into_inner().artifact_type;
+    
+    let artifact_type = match ArtifactType::try_from(artifact_type.as_str()) {
+        Ok(artifact_type) => artifact_type,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "Invalid artifact type",
+                "message": e.to_string()
+            })));
+        }
+    };
+    
+    if let Some(response) = check_project_access(&pool, project_id, user.user_id).await {
+        return Ok(response);
+    }
+    
+    let versions = match ArtifactService::list_versions(&pool, project_id, artifact_type).await {
+        Ok(versions) => versions,
+        Err(e) if db::is_statement_timeout(&e) => return Ok(database_timeout()),
+        Err(e) => {
+            tracing::error!("Failed to list artifact versions: {}", e);
+            return Ok(HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": "Failed to retrieve artifact versions"
+            })));
+        }
+    };
+    
+    // Default to the latest version, diffed against the one stored before it
+    let to = query.to.or_else(|| versions.first().map(|v| v.version));
+    let from = query.from.or_else(|| {
+        to.and_then(|to| versions.iter().map(|v| v.version).find(|&version| version < to))
+    });
+    
+    let (from, to) = match (from, to) {
+        (Some(from), Some(to)) => (from, to),
+        _ => {
+            return Ok(HttpResponse::NotFound().json(serde_json::json!({
+                "error": "Not enough artifact versions to diff"
+            })));
+        }
+    };
+    
+    let find = |version: i32| versions.iter().find(|v| v.version == version);
+    match (find(from), find(to)) {
+        (Some(old), Some(new)) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "artifact_type": artifact_type,
+            "from": from,
+            "to": to,
+            "diff": ArtifactService::unified_diff(old, new)
+        }))),
+        _ => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Artifact version not found"
+        }))),
+    }
 }
\ No newline at end of file
@@ -1,14 +1,31 @@
+use actix_web::{dev::Payload, error::InternalError, http::header, web, FromRequest, HttpRequest, HttpResponse};
 use anyhow::Result;
 use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 use argon2::password_hash::{rand_core::OsRng, SaltString};
+use chrono::{Duration, Utc};
+use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::PgPool;
+use std::future::{ready, Ready};
 use uuid::Uuid;
-use crate::models::{User, CreateUserRequest, LoginRequest, LoginResponse, SubscriptionTier};
+use crate::config::Config;
+use crate::db;
+use crate::models::{User, CreateUserRequest, LoginRequest, LoginResponse, RefreshTokenResponse, SubscriptionTier};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Claims {
+    pub sub: Uuid,
+    pub exp: i64,
+    pub iat: i64,
+}
 
 pub struct AuthService;
 
 impl AuthService {
     pub async fn register(pool: &PgPool, req: CreateUserRequest) -> Result<User> {
+        let email = Self::normalize_email(&req.email);
+        
         // Hash password
         let password_hash = Self::hash_password(&req.password)?;
         
@@ -22,10 +39,10 @@ impl AuthService {
                      subscription_tier, created_at, updated_at
             "#,
             user_id,
-            req.email,
+            email,
             password_hash,
             req.full_name,
-            "free"
+            SubscriptionTier::Free.as_str()
         )
         .fetch_one(pool)
         .await?;
@@ -42,15 +59,16 @@ impl AuthService {
         })
     }
     
-    pub async fn login(pool: &PgPool, req: LoginRequest) -> Result<LoginResponse> {
+    pub async fn login(pool: &PgPool, config: &Config, req: LoginRequest) -> Result<LoginResponse> {
+        // lower() keeps accounts registered before normalization reachable
         let user_row = sqlx::query!(
             r#"
             SELECT id, email, password_hash, full_name, stripe_customer_id,
                    subscription_tier, created_at, updated_at
             FROM users 
-            WHERE email = $1
+            WHERE lower(email) = $1
             "#,
-            req.email
+            Self::normalize_email(&req.email)
         )
         .fetch_optional(pool)
         .await?;
@@ -58,24 +76,24 @@ impl AuthService {
         match user_row {
             Some(user_row) => {
                 if Self::verify_password(&req.password, &user_row.password_hash)? {
-                    let token = Self::generate_jwt_token(&user_row.id)?;
+                    let token = Self::generate_jwt_token(&user_row.id, config)?;
+                    let refresh_token = Self::issue_refresh_token(pool, config, user_row.id).await?;
                     let user = User {
                         id: user_row.id,
                         email: user_row.email,
                         password_hash: user_row.password_hash,
                         full_name: user_row.full_name,
                         stripe_customer_id: user_row.stripe_customer_id,
-                        subscription_tier: match user_row.subscription_tier.as_str() {
-                            "starter" => SubscriptionTier::Starter,
-                            "professional" => SubscriptionTier::Professional,
-                            "enterprise" => SubscriptionTier::Enterprise,
-                            _ => SubscriptionTier::Free,
-                        },
+                        subscription_tier: SubscriptionTier::try_from(user_row.subscription_tier.as_str())
+                            .inspect_err(|e| {
+                                tracing::error!("Unexpected subscription tier in database: {}", e);
+                            })?,
                         created_at: user_row.created_at,
                         updated_at: user_row.updated_at,
                     };
                     Ok(LoginResponse {
                         token,
+                        refresh_token,
                         user: user.into(),
                     })
                 } else {
@@ -86,6 +104,129 @@ impl AuthService {
         }
     }
     
+    // Starts a new token family; returns the plaintext token, only its hash is stored
+    async fn issue_refresh_token(pool: &PgPool, config: &Config, user_id: Uuid) -> Result<String> {
+        let refresh_token = Self::generate_refresh_token();
+
+        // Prune this user's expired rows; revoked but unexpired ones stay so
+        // reuse of a rotated token can still be detected
+        sqlx::query!(
+            "DELETE FROM refresh_tokens WHERE user_id = $1 AND expires_at <= NOW()",
+            user_id
+        )
+        .execute(pool)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at)
+            VALUES ($1, $2, $1, $3, $4)
+            "#,
+            Uuid::new_v4(),
+            user_id,
+            Self::hash_refresh_token(&refresh_token),
+            Utc::now() + Duration::seconds(config.refresh_token_ttl_seconds)
+        )
+        .execute(pool)
+        .await?;
+        
+        Ok(refresh_token)
+    }
+    
+    // Rotates a refresh token and issues a new access token. Returns None when
+    // the token is unknown, expired or revoked; presenting a token that was
+    // already rotated revokes every token in its family.
+    pub async fn refresh(pool: &PgPool, config: &Config, refresh_token: &str) -> Result<Option<RefreshTokenResponse>> {
+        let token_hash = Self::hash_refresh_token(refresh_token);
+        let new_refresh_token = Self::generate_refresh_token();
+        let new_token_hash = Self::hash_refresh_token(&new_refresh_token);
+        let ttl_seconds = config.refresh_token_ttl_seconds;
+        
+        let user_id = db::with_transaction(pool, |tx| Box::pin(async move {
+            let current = sqlx::query!(
+                r#"
+                SELECT id, user_id, family_id, expires_at, revoked
+                FROM refresh_tokens
+                WHERE token_hash = $1
+                FOR UPDATE
+                "#,
+                token_hash
+            )
+            .fetch_optional(&mut **tx)
+            .await?;
+            
+            let current = match current {
+                Some(current) => current,
+                None => return Ok(None),
+            };
+            
+            if current.revoked {
+                tracing::warn!(
+                    user_id = %current.user_id,
+                    family_id = %current.family_id,
+                    "Refresh token reuse detected, revoking token family"
+                );
+                sqlx::query!(
+                    "UPDATE refresh_tokens SET revoked = TRUE WHERE family_id = $1",
+                    current.family_id
+                )
+                .execute(&mut **tx)
+                .await?;
+                return Ok(None);
+            }
+            
+            if current.expires_at <= Utc::now() {
+                return Ok(None);
+            }
+            
+            let replacement_id = Uuid::new_v4();
+            sqlx::query!(
+                r#"
+                INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at)
+                VALUES ($1, $2, $3, $4, $5)
+                "#,
+                replacement_id,
+                current.user_id,
+                current.family_id,
+                new_token_hash,
+                Utc::now() + Duration::seconds(ttl_seconds)
+            )
+            .execute(&mut **tx)
+            .await?;
+            
+            sqlx::query!(
+                "UPDATE refresh_tokens SET revoked = TRUE, replaced_by = $2 WHERE id = $1",
+                current.id,
+                replacement_id
+            )
+            .execute(&mut **tx)
+            .await?;
+            
+            Ok(Some(current.user_id))
+        }))
+        .await?;
+        
+        match user_id {
+            Some(user_id) => Ok(Some(RefreshTokenResponse {
+                token: Self::generate_jwt_token(&user_id, config)?,
+                refresh_token: new_refresh_token,
+            })),
+            None => Ok(None),
+        }
+    }
+    
+    fn generate_refresh_token() -> String {
+        format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+    }
+    
+    fn hash_refresh_token(refresh_token: &str) -> String {
+        format!("{:x}", Sha256::digest(refresh_token.as_bytes()))
+    }
+    
+    fn normalize_email(email: &str) -> String {
+        email.trim().to_lowercase()
+    }
+    
     fn hash_password(password: &str) -> Result<String> {
         let salt = SaltString::generate(&mut OsRng);
         let argon2 = Argon2::default();
@@ -105,8 +246,112 @@ impl AuthService {
             .is_ok())
     }
     
-    fn generate_jwt_token(_user_id: &Uuid) -> Result<String> {
-        // TODO: Implement proper JWT token generation
-        Ok("dummy-jwt-token".to_string())
+    pub async fn get_user(pool: &PgPool, user_id: Uuid) -> Result<Option<User>> {
+        let user_row = sqlx::query!(
+            r#"
+            SELECT id, email, password_hash, full_name, stripe_customer_id,
+                   subscription_tier, created_at, updated_at
+            FROM users 
+            WHERE id = $1
+            "#,
+            user_id
+        )
+        .fetch_optional(pool)
+        .await?;
+        
+        match user_row {
+            Some(user_row) => Ok(Some(User {
+                id: user_row.id,
+                email: user_row.email,
+                password_hash: user_row.password_hash,
+                full_name: user_row.full_name,
+                stripe_customer_id: user_row.stripe_customer_id,
+                subscription_tier: SubscriptionTier::try_from(user_row.subscription_tier.as_str())
+                    .inspect_err(|e| {
+                        tracing::error!("Unexpected subscription tier in database: {}", e);
+                    })?,
+                created_at: user_row.created_at,
+                updated_at: user_row.updated_at,
+            })),
+            None => Ok(None),
+        }
+    }
+    
+    fn generate_jwt_token(user_id: &Uuid, config: &Config) -> Result<String> {
+        let now = Utc::now().timestamp();
+        let claims = Claims {
+            sub: *user_id,
+            iat: now,
+            exp: now + config.jwt_ttl_seconds,
+        };
+        
+        let token = jsonwebtoken::encode(
+            &Header::new(Algorithm::HS256),
+            &claims,
+            &EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+        )?;
+        Ok(token)
     }
+    
+    pub fn validate_jwt_token(token: &str, config: &Config) -> Result<Claims, jsonwebtoken::errors::Error> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.leeway = 0;
+        
+        let data = jsonwebtoken::decode::<Claims>(
+            token,
+            &DecodingKey::from_secret(config.jwt_secret.as_bytes()),
+            &validation,
+        )?;
+        Ok(data.claims)
+    }
+}
+
+// Extracts the user id from a valid `Authorization: Bearer <jwt>` header,
+// rejecting the request with a 401 otherwise
+pub struct AuthenticatedUser {
+    pub user_id: Uuid,
+}
+
+impl AuthenticatedUser {
+    fn from_request_headers(req: &HttpRequest) -> Result<Self, actix_web::Error> {
+        let config = req
+            .app_data::<web::Data<Config>>()
+            .ok_or_else(|| actix_web::error::ErrorInternalServerError("Configuration missing"))?;
+        
+        let header_value = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .ok_or_else(|| unauthorized("Missing Authorization header"))?;
+        
+        let token = header_value
+            .to_str()
+            .ok()
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+            .ok_or_else(|| unauthorized("Authorization header must be a Bearer token"))?;
+        
+        match AuthService::validate_jwt_token(token, config) {
+            Ok(claims) => Ok(AuthenticatedUser { user_id: claims.sub }),
+            Err(e) if matches!(e.kind(), ErrorKind::ExpiredSignature) => Err(unauthorized("Token expired")),
+            Err(_) => Err(unauthorized("Invalid token")),
+        }
+    }
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+    
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Self::from_request_headers(req))
+    }
+}
+
+fn unauthorized(message: &'static str) -> actix_web::Error {
+    let response = HttpResponse::Unauthorized().json(serde_json::json!({
+        "error": "Unauthorized",
+        "message": message
+    }));
+    InternalError::from_response(message, response).into()
 }
\ No newline at end of file
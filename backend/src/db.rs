@@ -1,15 +1,81 @@
-use sqlx::{PgPool, postgres::PgPoolOptions};
+use sqlx::{ConnectOptions, Executor, PgPool, Postgres, Transaction, postgres::{PgConnectOptions, PgPoolOptions}};
 use anyhow::Result;
+use std::future::Future;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::time::Duration;
+use crate::config::Config;
+
+pub async fn create_pool(config: &Config) -> Result<PgPool> {
+    // Statements slower than the threshold are logged at warn level by sqlx
+    let connect_options = PgConnectOptions::from_str(&config.database_url)?
+        .log_slow_statements(
+            log::LevelFilter::Warn,
+            Duration::from_millis(config.slow_query_threshold_ms),
+        );
+
+    // Abort runaway statements server-side; 0 leaves the server default
+    let statement_timeout_ms = config.statement_timeout_ms;
 
-pub async fn create_pool(database_url: &str) -> Result<PgPool> {
     let pool = PgPoolOptions::new()
         .max_connections(10)
-        .connect(database_url)
+        .after_connect(move |conn, _meta| {
+            Box::pin(async move {
+                if statement_timeout_ms > 0 {
+                    conn.execute(format!("SET statement_timeout = {}", statement_timeout_ms).as_str())
+                        .await?;
+                }
+                Ok(())
+            })
+        })
+        .connect_with(connect_options)
         .await?;
     
     Ok(pool)
 }
 
+pub type TxFuture<'c, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'c>>;
+
+// Runs `f` inside a transaction: commits on Ok, rolls back on Err.
+// Callers box their async block, e.g. `|tx| Box::pin(async move { ... })`.
+pub async fn with_transaction<T, F>(pool: &PgPool, f: F) -> Result<T>
+where
+    F: for<'c> FnOnce(&'c mut Transaction<'static, Postgres>) -> TxFuture<'c, T>,
+{
+    let mut tx = pool.begin().await?;
+    
+    match f(&mut tx).await {
+        Ok(value) => {
+            tx.commit().await?;
+            Ok(value)
+        }
+        Err(e) => {
+            if let Err(rollback_err) = tx.rollback().await {
+                tracing::error!("Transaction rollback failed: {}", rollback_err);
+            }
+            Err(e)
+        }
+    }
+}
+
+// Postgres reports statement_timeout cancellations as query_canceled (57014)
+pub fn is_statement_timeout(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<sqlx::Error>()
+        .and_then(|e| e.as_database_error())
+        .and_then(|e| e.code())
+        .is_some_and(|code| code == "57014")
+}
+
+// Postgres unique_violation (23505), e.g. registering an email that exists
+pub fn is_unique_violation(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<sqlx::Error>()
+        .and_then(|e| e.as_database_error())
+        .and_then(|e| e.code())
+        .is_some_and(|code| code == "23505")
+}
+
 pub async fn run_migrations(_pool: &PgPool) -> Result<()> {
     // TODO: Run migrations when database is available
     tracing::info!("Database migrations skipped - run manually");
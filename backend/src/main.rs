@@ -1,10 +1,13 @@
-use actix_web::{web, App, HttpResponse, HttpServer, Result, middleware::Logger};
-use actix_cors::Cors;
-use std::env;
-use tracing_subscriber;
+use actix_web::{web, App, HttpResponse, HttpServer, Result, middleware::{from_fn, Compress, Logger}};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 
 mod api;
 mod config;
+mod middleware;
+mod provider_health;
+mod secrets;
+mod timestamps;
 
 use config::Config;
 
@@ -22,19 +25,24 @@ async fn main() -> std::io::Result<()> {
     tracing::info!("Starting MaxaMem backend server on {}", bind_address);
 
     HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_method()
-            .allow_any_header()
-            .max_age(3600);
-
         App::new()
             .app_data(web::Data::new(config.clone()))
-            .wrap(cors)
+            .app_data(api::errors::json_config())
+            .app_data(api::errors::query_config())
             .wrap(Logger::default())
-            .route("/health", web::get().to(health_check))
+            .wrap(from_fn(middleware::compression::skip_compression))
+            .wrap(Compress::default())
+            .wrap(from_fn(middleware::compression::strip_identity_encoding))
+            .wrap(from_fn(middleware::slow_requests::log_slow_requests))
+            .service(
+                web::resource("/health")
+                    .wrap(middleware::cors::public_cors())
+                    .route(web::get().to(health_check))
+            )
+            .configure(|cfg| provider_health::configure(cfg, &config))
             .service(
                 web::scope("/api/v1")
+                    .wrap(middleware::cors::api_cors(&config))
                     .service(api::auth::configure())
                     .service(api::projects::configure())
             )
@@ -44,11 +52,40 @@ async fn main() -> std::io::Result<()> {
     .await
 }
 
+#[derive(Serialize)]
+struct HealthResponse {
+    status: &'static str,
+    service: &'static str,
+    version: &'static str,
+    slow_requests: u64,
+    #[serde(serialize_with = "timestamps::serialize")]
+    timestamp: DateTime<Utc>,
+}
+
 async fn health_check() -> Result<HttpResponse> {
-    Ok(HttpResponse::Ok().json(serde_json::json!({
-        "status": "healthy",
-        "service": "maxamem-backend",
-        "version": env!("CARGO_PKG_VERSION"),
-        "timestamp": chrono::Utc::now()
-    })))
+    Ok(HttpResponse::Ok().json(HealthResponse {
+        status: "healthy",
+        service: "maxamem-backend",
+        version: env!("CARGO_PKG_VERSION"),
+        slow_requests: middleware::slow_requests::slow_request_count(),
+        timestamp: Utc::now(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test;
+
+    #[actix_web::test]
+    async fn health_timestamp_is_rfc3339_utc() {
+        let app = test::init_service(App::new().route("/health", web::get().to(health_check))).await;
+        let req = test::TestRequest::get().uri("/health").to_request();
+        let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+
+        let timestamp = body["timestamp"].as_str().expect("timestamp should be a string");
+        assert!(DateTime::parse_from_rfc3339(timestamp).is_ok());
+        assert!(timestamp.ends_with('Z'));
+        assert_eq!(timestamp.len(), "2024-01-01T00:00:00.000Z".len());
+    }
 }
\ No newline at end of file
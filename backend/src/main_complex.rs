@@ -1,5 +1,6 @@
-use actix_web::{web, App, HttpResponse, HttpServer, Result, middleware::Logger};
-use actix_cors::Cors;
+use actix_web::{web, App, HttpResponse, HttpServer, Result, middleware::{from_fn, Compress, Logger}};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
 use sqlx::PgPool;
 use std::env;
 use tracing_subscriber;
@@ -10,6 +11,10 @@ mod auth;
 mod models;
 mod db;
 mod config;
+mod middleware;
+mod provider_health;
+mod secrets;
+mod timestamps;
 mod services;
 
 use config::Config;
@@ -22,9 +27,11 @@ async fn main() -> std::io::Result<()> {
     // Load configuration
     dotenv::dotenv().ok();
     let config = Config::from_env().expect("Failed to load configuration");
+    services::artifact::ArtifactService::validate_version_limits(&config)
+        .expect("Failed to load configuration");
 
     // Connect to database
-    let pool = db::create_pool(&config.database_url)
+    let pool = db::create_pool(&config)
         .await
         .expect("Failed to create database pool");
 
@@ -38,23 +45,29 @@ async fn main() -> std::io::Result<()> {
     tracing::info!("Starting MaxaMem backend server on {}", bind_address);
 
     HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_method()
-            .allow_any_header()
-            .max_age(3600);
-
         App::new()
             .app_data(web::Data::new(pool.clone()))
             .app_data(web::Data::new(config.clone()))
-            .wrap(cors)
+            .app_data(api::errors::json_config())
+            .app_data(api::errors::query_config())
             .wrap(TracingLogger::default())
             .wrap(Logger::default())
-            .route("/health", web::get().to(health_check))
+            .wrap(from_fn(middleware::compression::skip_compression))
+            .wrap(Compress::default())
+            .wrap(from_fn(middleware::compression::strip_identity_encoding))
+            .wrap(from_fn(middleware::slow_requests::log_slow_requests))
+            .service(
+                web::resource("/health")
+                    .wrap(middleware::cors::public_cors())
+                    .route(web::get().to(health_check))
+            )
+            .configure(|cfg| provider_health::configure(cfg, &config))
             .service(
                 web::scope("/api/v1")
+                    .wrap(middleware::cors::api_cors(&config))
                     .service(api::auth::configure())
                     .service(api::projects::configure())
+                    .service(api::projects::configure_templates())
             )
     })
     .bind(&bind_address)?
@@ -62,11 +75,22 @@ async fn main() -> std::io::Result<()> {
     .await
 }
 
+#[derive(Serialize)]
+struct HealthResponse {
+    status: &'static str,
+    service: &'static str,
+    version: &'static str,
+    slow_requests: u64,
+    #[serde(serialize_with = "timestamps::serialize")]
+    timestamp: DateTime<Utc>,
+}
+
 async fn health_check() -> Result<HttpResponse> {
-    Ok(HttpResponse::Ok().json(serde_json::json!({
-        "status": "healthy",
-        "service": "maxamem-backend",
-        "version": env!("CARGO_PKG_VERSION"),
-        "timestamp": chrono::Utc::now()
-    })))
+    Ok(HttpResponse::Ok().json(HealthResponse {
+        status: "healthy",
+        service: "maxamem-backend",
+        version: env!("CARGO_PKG_VERSION"),
+        slow_requests: middleware::slow_requests::slow_request_count(),
+        timestamp: Utc::now(),
+    }))
 }
\ No newline at end of file
@@ -0,0 +1,120 @@
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderValue, CONTENT_ENCODING, CONTENT_TYPE};
+use actix_web::middleware::Next;
+use actix_web::{web, Error};
+
+use crate::config::Config;
+
+// Runs inside `Compress`: an explicit identity encoding makes it pass the
+// response through untouched. `strip_identity_encoding` removes the marker
+// again on the way out.
+pub async fn skip_compression(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let config = req.app_data::<web::Data<Config>>().cloned();
+    let mut res = next.call(req).await?;
+
+    let Some(config) = config else {
+        return Ok(res);
+    };
+
+    if res.headers().contains_key(CONTENT_ENCODING) {
+        return Ok(res);
+    }
+
+    let too_small = match res.response().body().size() {
+        BodySize::Sized(size) => size < config.compression_min_size,
+        _ => false,
+    };
+
+    let excluded_type = res
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| {
+            config
+                .compression_excluded_types
+                .iter()
+                .any(|excluded| content_type.starts_with(excluded.as_str()))
+        });
+
+    if too_small || excluded_type {
+        res.headers_mut()
+            .insert(CONTENT_ENCODING, HeaderValue::from_static("identity"));
+    }
+
+    Ok(res)
+}
+
+
+// Runs outside `Compress`. `identity` must not appear in Content-Encoding
+// (RFC 9110 8.4.1), so the marker set by `skip_compression` is dropped.
+pub async fn strip_identity_encoding(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let mut res = next.call(req).await?;
+
+    let is_identity = res
+        .headers()
+        .get(CONTENT_ENCODING)
+        .is_some_and(|value| value.as_bytes().eq_ignore_ascii_case(b"identity"));
+    if is_identity {
+        res.headers_mut().remove(CONTENT_ENCODING);
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::ACCEPT_ENCODING;
+    use actix_web::middleware::{from_fn, Compress};
+    use actix_web::{test, App, HttpResponse};
+
+    async fn content_encoding(path: &str) -> Option<String> {
+        let config = Config::for_tests();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(config))
+                .wrap(from_fn(skip_compression))
+                .wrap(Compress::default())
+                .wrap(from_fn(strip_identity_encoding))
+                .route("/small", web::get().to(|| async { HttpResponse::Ok().json("ok") }))
+                .route("/large", web::get().to(|| async { HttpResponse::Ok().body("a".repeat(4096)) }))
+                .route("/image", web::get().to(|| async {
+                    HttpResponse::Ok().content_type("image/png").body(vec![0u8; 4096])
+                })),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri(path)
+            .insert_header((ACCEPT_ENCODING, "gzip"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert!(res.status().is_success());
+        res.headers()
+            .get(CONTENT_ENCODING)
+            .map(|value| value.to_str().unwrap().to_string())
+    }
+
+    #[actix_web::test]
+    async fn small_responses_are_not_compressed() {
+        assert_eq!(content_encoding("/small").await, None);
+    }
+
+    #[actix_web::test]
+    async fn large_responses_are_compressed() {
+        assert_eq!(content_encoding("/large").await.as_deref(), Some("gzip"));
+    }
+
+    #[actix_web::test]
+    async fn excluded_types_are_not_compressed() {
+        assert_eq!(content_encoding("/image").await, None);
+    }
+}
\ No newline at end of file
@@ -0,0 +1,29 @@
+use actix_cors::Cors;
+
+use crate::config::Config;
+
+// Authenticated API: only the configured app origins ("*" allows any)
+pub fn api_cors(config: &Config) -> Cors {
+    let cors = Cors::default()
+        .allowed_methods(vec!["GET", "POST", "PUT", "DELETE"])
+        .allowed_headers(vec!["Authorization", "Content-Type"])
+        .max_age(3600);
+
+    if config.cors_allowed_origins.iter().any(|origin| origin == "*") {
+        return cors.allow_any_origin();
+    }
+
+    config
+        .cors_allowed_origins
+        .iter()
+        .fold(cors, |cors, origin| cors.allowed_origin(origin))
+}
+
+// Public, unauthenticated endpoints can be called from anywhere
+pub fn public_cors() -> Cors {
+    Cors::default()
+        .allow_any_origin()
+        .allowed_methods(vec!["GET", "POST"])
+        .allow_any_header()
+        .max_age(3600)
+}
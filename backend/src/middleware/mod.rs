@@ -0,0 +1,3 @@
+pub mod compression;
+pub mod cors;
+pub mod slow_requests;
\ No newline at end of file
@@ -0,0 +1,103 @@
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::{web, Error};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::config::Config;
+
+const REQUEST_ID_HEADER: &str = "x-request-id";
+// Longer client-supplied ids are replaced rather than written to every log line
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+static SLOW_REQUESTS: AtomicU64 = AtomicU64::new(0);
+
+pub fn slow_request_count() -> u64 {
+    SLOW_REQUESTS.load(Ordering::Relaxed)
+}
+
+pub async fn log_slow_requests(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, Error> {
+    let threshold = req
+        .app_data::<web::Data<Config>>()
+        .map(|config| Duration::from_millis(config.slow_request_threshold_ms));
+
+    // Reuse the caller's request id when present so slow-request warnings
+    // can be matched against client or proxy logs
+    let request_id = req
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty() && value.len() <= MAX_REQUEST_ID_LEN)
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    let started = Instant::now();
+    let mut res = next.call(req).await?;
+    let elapsed = started.elapsed();
+
+    if let Some(threshold) = threshold {
+        if elapsed > threshold {
+            SLOW_REQUESTS.fetch_add(1, Ordering::Relaxed);
+
+            let request = res.request();
+            tracing::warn!(
+                request_id = %request_id,
+                method = %request.method(),
+                route = %request.match_pattern().unwrap_or_else(|| request.path().to_string()),
+                status = res.status().as_u16(),
+                elapsed_ms = elapsed.as_millis() as u64,
+                threshold_ms = threshold.as_millis() as u64,
+                "Slow request"
+            );
+        }
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        res.headers_mut()
+            .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+
+    Ok(res)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::middleware::from_fn;
+    use actix_web::{test, App, HttpResponse};
+
+    async fn echoed_request_id(request_id: &str) -> String {
+        let app = test::init_service(
+            App::new()
+                .wrap(from_fn(log_slow_requests))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .insert_header((REQUEST_ID_HEADER, request_id))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        res.headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .expect("response should carry a request id")
+            .to_string()
+    }
+
+    #[actix_web::test]
+    async fn replaces_overlong_request_ids() {
+        assert_eq!(echoed_request_id("client-id-1").await, "client-id-1");
+
+        let long_id = "a".repeat(MAX_REQUEST_ID_LEN + 1);
+        let replaced = echoed_request_id(&long_id).await;
+        assert!(Uuid::parse_str(&replaced).is_ok());
+    }
+}
\ No newline at end of file
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use sqlx::types::Uuid;
 use chrono::{DateTime, Utc};
+use std::fmt;
 
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct User {
@@ -10,11 +11,13 @@ pub struct User {
     pub full_name: String,
     pub stripe_customer_id: Option<String>,
     pub subscription_tier: SubscriptionTier,
+    #[serde(serialize_with = "crate::timestamps::serialize")]
     pub created_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::timestamps::serialize")]
     pub updated_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "subscription_tier", rename_all = "snake_case")]
 pub enum SubscriptionTier {
     Free,
@@ -23,6 +26,47 @@ pub enum SubscriptionTier {
     Enterprise,
 }
 
+impl SubscriptionTier {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubscriptionTier::Free => "free",
+            SubscriptionTier::Starter => "starter",
+            SubscriptionTier::Professional => "professional",
+            SubscriptionTier::Enterprise => "enterprise",
+        }
+    }
+    
+    // Projects a user may start per calendar month; None means unlimited
+    pub fn monthly_project_limit(&self) -> Option<i64> {
+        match self {
+            SubscriptionTier::Free => Some(1),
+            SubscriptionTier::Starter => Some(10),
+            SubscriptionTier::Professional => Some(100),
+            SubscriptionTier::Enterprise => None,
+        }
+    }
+}
+
+impl fmt::Display for SubscriptionTier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for SubscriptionTier {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "free" => Ok(SubscriptionTier::Free),
+            "starter" => Ok(SubscriptionTier::Starter),
+            "professional" => Ok(SubscriptionTier::Professional),
+            "enterprise" => Ok(SubscriptionTier::Enterprise),
+            _ => anyhow::bail!("Unknown subscription tier: {}", value),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Project {
     pub id: Uuid,
@@ -33,7 +77,11 @@ pub struct Project {
     pub progress: i32,
     pub repository_url: Option<String>,
     pub technologies: Vec<String>,
+    pub is_template: bool,
+    pub visibility: ProjectVisibility,
+    #[serde(serialize_with = "crate::timestamps::serialize")]
     pub created_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::timestamps::serialize")]
     pub updated_at: DateTime<Utc>,
 }
 
@@ -46,16 +94,83 @@ pub enum ProjectStatus {
     Failed,
 }
 
+impl ProjectStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProjectStatus::Pending => "pending",
+            ProjectStatus::Generating => "generating",
+            ProjectStatus::Complete => "complete",
+            ProjectStatus::Failed => "failed",
+        }
+    }
+}
+
+impl fmt::Display for ProjectStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for ProjectStatus {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "pending" => Ok(ProjectStatus::Pending),
+            "generating" => Ok(ProjectStatus::Generating),
+            "complete" => Ok(ProjectStatus::Complete),
+            "failed" => Ok(ProjectStatus::Failed),
+            _ => anyhow::bail!("Unknown project status: {}", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "project_visibility", rename_all = "snake_case")]
+pub enum ProjectVisibility {
+    Private,
+    Public,
+}
+
+impl ProjectVisibility {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProjectVisibility::Private => "private",
+            ProjectVisibility::Public => "public",
+        }
+    }
+}
+
+impl fmt::Display for ProjectVisibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for ProjectVisibility {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "private" => Ok(ProjectVisibility::Private),
+            "public" => Ok(ProjectVisibility::Public),
+            _ => anyhow::bail!("Unknown project visibility: {}", value),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
 pub struct GenerationArtifact {
     pub id: Uuid,
     pub project_id: Uuid,
     pub artifact_type: ArtifactType,
     pub content: serde_json::Value,
+    pub version: i32,
+    #[serde(serialize_with = "crate::timestamps::serialize")]
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "artifact_type", rename_all = "snake_case")]
 pub enum ArtifactType {
     DevPlan,
@@ -66,6 +181,41 @@ pub enum ArtifactType {
     DirectoryTree,
 }
 
+impl ArtifactType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ArtifactType::DevPlan => "dev_plan",
+            ArtifactType::TechArchitecture => "tech_architecture",
+            ArtifactType::BlueprintJson => "blueprint_json",
+            ArtifactType::MainReadme => "main_readme",
+            ArtifactType::CommunicationSchema => "communication_schema",
+            ArtifactType::DirectoryTree => "directory_tree",
+        }
+    }
+}
+
+impl fmt::Display for ArtifactType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<&str> for ArtifactType {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "dev_plan" => Ok(ArtifactType::DevPlan),
+            "tech_architecture" => Ok(ArtifactType::TechArchitecture),
+            "blueprint_json" => Ok(ArtifactType::BlueprintJson),
+            "main_readme" => Ok(ArtifactType::MainReadme),
+            "communication_schema" => Ok(ArtifactType::CommunicationSchema),
+            "directory_tree" => Ok(ArtifactType::DirectoryTree),
+            _ => anyhow::bail!("Unknown artifact type: {}", value),
+        }
+    }
+}
+
 // DTOs for API requests/responses
 #[derive(Debug, Deserialize)]
 pub struct CreateUserRequest {
@@ -83,9 +233,21 @@ pub struct LoginRequest {
 #[derive(Debug, Serialize)]
 pub struct LoginResponse {
     pub token: String,
+    pub refresh_token: String,
     pub user: UserResponse,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RefreshTokenResponse {
+    pub token: String,
+    pub refresh_token: String,
+}
+
 #[derive(Debug, Serialize)]
 pub struct UserResponse {
     pub id: Uuid,
@@ -101,6 +263,108 @@ pub struct CreateProjectRequest {
     pub technologies: Vec<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UpdateTemplateRequest {
+    pub is_template: bool,
+    pub visibility: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TemplateListQuery {
+    #[serde(default = "default_page_size")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectListQuery {
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub status: Option<String>,
+    #[serde(default)]
+    pub sort: ProjectSort,
+    #[serde(default)]
+    pub order: SortOrder,
+    #[serde(default = "default_page_size")]
+    pub limit: i64,
+    #[serde(default)]
+    pub offset: i64,
+}
+
+fn default_page_size() -> i64 {
+    20
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProjectSort {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+}
+
+impl ProjectSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProjectSort::CreatedAt => "created_at",
+            ProjectSort::UpdatedAt => "updated_at",
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProjectListResponse {
+    pub projects: Vec<ProjectResponse>,
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuotaExceededResponse {
+    pub error: String,
+    pub tier: SubscriptionTier,
+    pub limit: i64,
+    pub usage: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkDeleteRequest {
+    pub ids: Vec<Uuid>,
+    #[serde(default)]
+    pub hard: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteProjectQuery {
+    #[serde(default)]
+    pub hard: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkDeleteResponse {
+    pub deleted: usize,
+    pub skipped: Vec<Uuid>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ProjectResponse {
     pub id: Uuid,
@@ -110,7 +374,11 @@ pub struct ProjectResponse {
     pub progress: i32,
     pub repository_url: Option<String>,
     pub technologies: Vec<String>,
+    pub is_template: bool,
+    pub visibility: ProjectVisibility,
+    #[serde(serialize_with = "crate::timestamps::serialize")]
     pub created_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::timestamps::serialize")]
     pub updated_at: DateTime<Utc>,
 }
 
@@ -135,6 +403,35 @@ impl From<Project> for ProjectResponse {
             progress: project.progress,
             repository_url: project.repository_url,
             technologies: project.technologies,
+            is_template: project.is_template,
+            visibility: project.visibility,
+            created_at: project.created_at,
+            updated_at: project.updated_at,
+        }
+    }
+}
+
+// Templates are listed to every user, so owner-specific details such as the
+// repository URL are left out
+#[derive(Debug, Serialize)]
+pub struct TemplateResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub technologies: Vec<String>,
+    #[serde(serialize_with = "crate::timestamps::serialize")]
+    pub created_at: DateTime<Utc>,
+    #[serde(serialize_with = "crate::timestamps::serialize")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<Project> for TemplateResponse {
+    fn from(project: Project) -> Self {
+        TemplateResponse {
+            id: project.id,
+            name: project.name,
+            description: project.description,
+            technologies: project.technologies,
             created_at: project.created_at,
             updated_at: project.updated_at,
         }
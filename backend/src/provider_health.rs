@@ -0,0 +1,220 @@
+use actix_web::{web, HttpResponse, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use crate::config::Config;
+use crate::middleware::cors::public_cors;
+use crate::timestamps;
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+const OPENAI_MODELS_URL: &str = "https://api.openai.com/v1/models";
+const CLAUDE_MODELS_URL: &str = "https://api.anthropic.com/v1/models";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+// Last probe result; the lock is held while probing so concurrent callers
+// wait for one round of requests instead of each starting their own
+static CACHE: Mutex<Option<(Instant, ProvidersReport)>> = Mutex::const_new(None);
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum ProviderStatus {
+    Reachable,
+    Unauthorized,
+    Error,
+    Unreachable,
+    NotConfigured,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ProviderCheck {
+    status: ProviderStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latency_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct ProvidersReport {
+    openai: ProviderCheck,
+    claude: ProviderCheck,
+    #[serde(serialize_with = "timestamps::serialize")]
+    checked_at: DateTime<Utc>,
+}
+
+// Probing spends the deployment's provider keys on every cache miss, so the
+// endpoint is only mounted when PROVIDER_HEALTH_ENABLED=true
+pub fn configure(cfg: &mut web::ServiceConfig, config: &Config) {
+    if config.provider_health_enabled {
+        cfg.service(
+            web::resource("/health/providers")
+                .wrap(public_cors())
+                .route(web::get().to(check_providers))
+        );
+    }
+}
+
+// Lists models on each provider, which costs no tokens. Always answers 200:
+// this reports on upstream dependencies and is kept out of the readiness
+// check so a provider blip doesn't pull the server from rotation.
+pub async fn check_providers(config: web::Data<Config>) -> Result<HttpResponse> {
+    let mut cache = CACHE.lock().await;
+
+    if let Some((probed_at, report)) = cache.as_ref() {
+        if probed_at.elapsed() < CACHE_TTL {
+            return Ok(HttpResponse::Ok().json(report));
+        }
+    }
+
+    let report = probe(&config).await;
+    *cache = Some((Instant::now(), report.clone()));
+    Ok(HttpResponse::Ok().json(report))
+}
+
+async fn probe(config: &Config) -> ProvidersReport {
+    let client = match reqwest::Client::builder().timeout(PROBE_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::error!("Failed to build provider health client: {}", e);
+            let failed = ProviderCheck {
+                status: ProviderStatus::Error,
+                latency_ms: None,
+                message: Some("HTTP client unavailable".to_string()),
+            };
+            return ProvidersReport {
+                openai: failed.clone(),
+                claude: failed,
+                checked_at: Utc::now(),
+            };
+        }
+    };
+
+    let openai = config.openai_api_key.as_ref().map(|key| {
+        client.get(OPENAI_MODELS_URL).bearer_auth(key)
+    });
+    let claude = config.claude_api_key.as_ref().map(|key| {
+        client
+            .get(CLAUDE_MODELS_URL)
+            .header("x-api-key", key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+    });
+
+    let (openai, claude) = tokio::join!(check(openai), check(claude));
+
+    ProvidersReport {
+        openai,
+        claude,
+        checked_at: Utc::now(),
+    }
+}
+
+async fn check(request: Option<reqwest::RequestBuilder>) -> ProviderCheck {
+    let request = match request {
+        Some(request) => request,
+        None => {
+            return ProviderCheck {
+                status: ProviderStatus::NotConfigured,
+                latency_ms: None,
+                message: None,
+            };
+        }
+    };
+
+    let started = Instant::now();
+    let result = request.send().await;
+    let latency_ms = Some(started.elapsed().as_millis() as u64);
+
+    match result {
+        Ok(response) if response.status().is_success() => ProviderCheck {
+            status: ProviderStatus::Reachable,
+            latency_ms,
+            message: None,
+        },
+        Ok(response) => {
+            let status = response.status();
+            ProviderCheck {
+                status: if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+                    ProviderStatus::Unauthorized
+                } else {
+                    ProviderStatus::Error
+                },
+                latency_ms,
+                message: Some(format!("HTTP {}", status.as_u16())),
+            }
+        }
+        Err(e) => ProviderCheck {
+            status: ProviderStatus::Unreachable,
+            latency_ms,
+            message: Some(if e.is_timeout() {
+                "Timed out".to_string()
+            } else {
+                "Connection failed".to_string()
+            }),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    // Answers a single request with the given status, or never answers
+    async fn stub_provider(status: Option<u16>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buffer = [0; 1024];
+            let _ = socket.read(&mut buffer).await;
+            match status {
+                Some(status) => {
+                    let response = format!("HTTP/1.1 {} Stub\r\ncontent-length: 0\r\n\r\n", status);
+                    socket.write_all(response.as_bytes()).await.unwrap();
+                }
+                None => tokio::time::sleep(Duration::from_secs(5)).await,
+            }
+        });
+
+        format!("http://{}/v1/models", address)
+    }
+
+    async fn check_stub(status: Option<u16>) -> ProviderCheck {
+        let url = stub_provider(status).await;
+        let request = reqwest::Client::new()
+            .get(url)
+            .timeout(Duration::from_millis(100));
+        check(Some(request)).await
+    }
+
+    #[actix_web::test]
+    async fn maps_provider_responses_to_statuses() {
+        let result = check_stub(Some(200)).await;
+        assert!(matches!(result.status, ProviderStatus::Reachable));
+        assert!(result.message.is_none());
+
+        for status in [401, 403] {
+            let result = check_stub(Some(status)).await;
+            assert!(matches!(result.status, ProviderStatus::Unauthorized));
+            assert_eq!(result.message, Some(format!("HTTP {}", status)));
+        }
+
+        let result = check_stub(Some(500)).await;
+        assert!(matches!(result.status, ProviderStatus::Error));
+        assert_eq!(result.message.as_deref(), Some("HTTP 500"));
+
+        let result = check_stub(None).await;
+        assert!(matches!(result.status, ProviderStatus::Unreachable));
+        assert_eq!(result.message.as_deref(), Some("Timed out"));
+
+        let result = check(None).await;
+        assert!(matches!(result.status, ProviderStatus::NotConfigured));
+        assert!(result.latency_ms.is_none());
+    }
+}
\ No newline at end of file
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::sync::Mutex;
+
+// Resolves secret-bearing configuration values by name, e.g. "JWT_SECRET".
+// Managed backends (Vault, AWS Secrets Manager) implement this and get
+// registered in `from_env`.
+pub trait SecretProvider: Send + Sync {
+    fn get_secret(&self, name: &str) -> Result<Option<String>, Box<dyn Error>>;
+}
+
+// Reads `NAME` from the environment, falling back to the file at `NAME_FILE`
+// (Docker/Kubernetes secret mounts)
+pub struct EnvSecretProvider;
+
+impl SecretProvider for EnvSecretProvider {
+    fn get_secret(&self, name: &str) -> Result<Option<String>, Box<dyn Error>> {
+        if let Ok(value) = env::var(name) {
+            return Ok(Some(value));
+        }
+
+        match env::var(format!("{}_FILE", name)) {
+            Ok(path) => {
+                let value = fs::read_to_string(&path)
+                    .map_err(|e| format!("Failed to read {}_FILE ({}): {}", name, path, e))?;
+                Ok(Some(value.trim_end().to_string()))
+            }
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+// Each secret is fetched from the wrapped provider at most once
+pub struct CachedSecretProvider {
+    inner: Box<dyn SecretProvider>,
+    cache: Mutex<HashMap<String, Option<String>>>,
+}
+
+impl CachedSecretProvider {
+    pub fn new(inner: Box<dyn SecretProvider>) -> Self {
+        Self {
+            inner,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl SecretProvider for CachedSecretProvider {
+    fn get_secret(&self, name: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let mut cache = self.cache.lock().map_err(|_| "Secret cache lock poisoned")?;
+
+        if let Some(value) = cache.get(name) {
+            return Ok(value.clone());
+        }
+
+        let value = self.inner.get_secret(name)?;
+        cache.insert(name.to_string(), value.clone());
+        Ok(value)
+    }
+}
+
+// Picks the backend named by SECRET_PROVIDER (default "env")
+pub fn from_env() -> Result<CachedSecretProvider, Box<dyn Error>> {
+    let provider: Box<dyn SecretProvider> = match env::var("SECRET_PROVIDER")
+        .unwrap_or_else(|_| "env".to_string())
+        .as_str()
+    {
+        "env" => Box::new(EnvSecretProvider),
+        other => return Err(format!("Unsupported SECRET_PROVIDER: {}", other).into()),
+    };
+
+    Ok(CachedSecretProvider::new(provider))
+}
\ No newline at end of file
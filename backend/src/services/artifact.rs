@@ -0,0 +1,225 @@
+use anyhow::Result;
+use std::io::Write;
+use sqlx::PgPool;
+use uuid::Uuid;
+use crate::config::Config;
+use crate::db;
+use crate::models::{ArtifactType, GenerationArtifact};
+
+pub struct ArtifactService;
+
+impl ArtifactService {
+    pub async fn save_artifact(
+        pool: &PgPool,
+        config: &Config,
+        project_id: Uuid,
+        artifact_type: ArtifactType,
+        content: serde_json::Value,
+    ) -> Result<GenerationArtifact> {
+        let artifact_id = Uuid::new_v4();
+        let limit = Self::version_limit(config, &artifact_type);
+
+        let artifact_row = db::with_transaction(pool, |tx| Box::pin(async move {
+            let artifact_row = sqlx::query!(
+                r#"
+                INSERT INTO generation_artifacts (id, project_id, artifact_type, content, version)
+                VALUES (
+                    $1, $2, $3, $4,
+                    COALESCE(
+                        (SELECT MAX(version) FROM generation_artifacts
+                         WHERE project_id = $2 AND artifact_type = $3),
+                        0
+                    ) + 1
+                )
+                RETURNING id, project_id, content, version, created_at
+                "#,
+                artifact_id,
+                project_id,
+                artifact_type.as_str(),
+                content
+            )
+            .fetch_one(&mut **tx)
+            .await?;
+
+            // Prune versions beyond the cap; the one just inserted always survives
+            sqlx::query!(
+                r#"
+                DELETE FROM generation_artifacts
+                WHERE project_id = $1 AND artifact_type = $2 AND version <= $3
+                "#,
+                project_id,
+                artifact_type.as_str(),
+                artifact_row.version - limit
+            )
+            .execute(&mut **tx)
+            .await?;
+
+            Ok(artifact_row)
+        }))
+        .await?;
+
+        Ok(GenerationArtifact {
+            id: artifact_row.id,
+            project_id: artifact_row.project_id,
+            artifact_type,
+            content: artifact_row.content,
+            version: artifact_row.version,
+            created_at: artifact_row.created_at,
+        })
+    }
+
+    // Stores generator output for a step as a new version of its artifact
+    pub async fn save_document(
+        pool: &PgPool,
+        config: &Config,
+        project_id: Uuid,
+        artifact_type: ArtifactType,
+        raw: &str,
+    ) -> Result<GenerationArtifact> {
+        let content = Self::document_content(artifact_type, raw)?;
+        Self::save_artifact(pool, config, project_id, artifact_type, content).await
+    }
+
+    // Blueprint and schema output is kept as parsed JSON so it can be queried
+    // into; markdown and text artifacts are wrapped as `{"markdown": ...}`
+    pub fn document_content(artifact_type: ArtifactType, raw: &str) -> Result<serde_json::Value> {
+        match artifact_type {
+            ArtifactType::BlueprintJson | ArtifactType::CommunicationSchema => serde_json::from_str(raw)
+                .map_err(|e| anyhow::anyhow!("{} output is not valid JSON: {}", artifact_type, e)),
+            ArtifactType::DevPlan
+            | ArtifactType::TechArchitecture
+            | ArtifactType::MainReadme
+            | ArtifactType::DirectoryTree => Ok(serde_json::json!({ "markdown": raw })),
+        }
+    }
+
+    pub async fn list_versions(
+        pool: &PgPool,
+        project_id: Uuid,
+        artifact_type: ArtifactType,
+    ) -> Result<Vec<GenerationArtifact>> {
+        let artifact_rows = sqlx::query!(
+            r#"
+            SELECT id, project_id, content, version, created_at
+            FROM generation_artifacts
+            WHERE project_id = $1 AND artifact_type = $2
+            ORDER BY version DESC
+            "#,
+            project_id,
+            artifact_type.as_str()
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let artifacts = artifact_rows
+            .into_iter()
+            .map(|row| GenerationArtifact {
+                id: row.id,
+                project_id: row.project_id,
+                artifact_type,
+                content: row.content,
+                version: row.version,
+                created_at: row.created_at,
+            })
+            .collect();
+
+        Ok(artifacts)
+    }
+
+    // Newest version of each artifact type stored for the project
+    pub async fn latest_artifacts(pool: &PgPool, project_id: Uuid) -> Result<Vec<GenerationArtifact>> {
+        let artifact_rows = sqlx::query!(
+            r#"
+            SELECT DISTINCT ON (artifact_type)
+                id, project_id, artifact_type::text AS "artifact_type!", content, version, created_at
+            FROM generation_artifacts
+            WHERE project_id = $1
+            ORDER BY artifact_type, version DESC
+            "#,
+            project_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        artifact_rows
+            .into_iter()
+            .map(|row| {
+                Ok(GenerationArtifact {
+                    id: row.id,
+                    project_id: row.project_id,
+                    artifact_type: ArtifactType::try_from(row.artifact_type.as_str())?,
+                    content: row.content,
+                    version: row.version,
+                    created_at: row.created_at,
+                })
+            })
+            .collect()
+    }
+
+    pub fn export_zip(artifacts: &[GenerationArtifact]) -> Result<Vec<u8>> {
+        let mut writer = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated);
+
+        for artifact in artifacts {
+            writer.start_file(Self::export_path(artifact.artifact_type), options)?;
+            writer.write_all(Self::artifact_text(&artifact.content).as_bytes())?;
+        }
+
+        Ok(writer.finish()?.into_inner())
+    }
+
+    fn export_path(artifact_type: ArtifactType) -> &'static str {
+        match artifact_type {
+            ArtifactType::DevPlan => "docs/dev_plan.md",
+            ArtifactType::TechArchitecture => "docs/architecture.md",
+            ArtifactType::BlueprintJson => "blueprint.json",
+            ArtifactType::MainReadme => "README.md",
+            ArtifactType::CommunicationSchema => "schema/communication_schema.json",
+            ArtifactType::DirectoryTree => "directory_tree.txt",
+        }
+    }
+
+    pub fn unified_diff(from: &GenerationArtifact, to: &GenerationArtifact) -> String {
+        let old_text = Self::artifact_text(&from.content);
+        let new_text = Self::artifact_text(&to.content);
+
+        similar::TextDiff::from_lines(&old_text, &new_text)
+            .unified_diff()
+            .context_radius(3)
+            .header(&format!("v{}", from.version), &format!("v{}", to.version))
+            .to_string()
+    }
+
+    // Markdown artifacts diff as their text, structured ones as pretty JSON
+    fn artifact_text(content: &serde_json::Value) -> String {
+        match content {
+            serde_json::Value::String(text) => text.clone(),
+            serde_json::Value::Object(fields) if fields.len() == 1 && fields.contains_key("markdown") => {
+                fields["markdown"].as_str().unwrap_or_default().to_string()
+            }
+            other => serde_json::to_string_pretty(other).unwrap_or_default(),
+        }
+    }
+
+    // Rejects ARTIFACT_VERSION_LIMITS keys that name no artifact type, which
+    // would otherwise be ignored silently
+    pub fn validate_version_limits(config: &Config) -> Result<()> {
+        for key in config.artifact_version_limits.keys() {
+            ArtifactType::try_from(key.as_str())
+                .map_err(|e| anyhow::anyhow!("Invalid ARTIFACT_VERSION_LIMITS: {}", e))?;
+        }
+        Ok(())
+    }
+
+    // Limits are validated to be at least 1 when the config is loaded
+    fn version_limit(config: &Config, artifact_type: &ArtifactType) -> i32 {
+        let limit = config
+            .artifact_version_limits
+            .get(artifact_type.as_str())
+            .copied()
+            .unwrap_or(config.max_artifact_versions);
+
+        limit.min(i32::MAX as i64) as i32
+    }
+}
\ No newline at end of file
@@ -1 +1,2 @@
+pub mod artifact;
 pub mod project;
\ No newline at end of file
@@ -1,138 +1,442 @@
 use anyhow::Result;
-use sqlx::PgPool;
+use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
-use crate::models::{Project, CreateProjectRequest, ProjectStatus};
+use crate::db;
+use crate::models::{Project, CreateProjectRequest, ProjectListQuery, ProjectStatus, ProjectVisibility, QuotaExceededResponse, SubscriptionTier, User};
 
 pub struct ProjectService;
 
 impl ProjectService {
+    // Creates the project unless the user's monthly quota is used up, in
+    // which case nothing is written and the inner Err describes the limit
     pub async fn create_project(
         pool: &PgPool,
-        user_id: Uuid,
+        user: &User,
         req: CreateProjectRequest,
-    ) -> Result<Project> {
+    ) -> Result<Result<Project, QuotaExceededResponse>> {
+        let user_id = user.id;
+        let tier = user.subscription_tier;
         let project_id = Uuid::new_v4();
         
-        let project_row = sqlx::query!(
+        db::with_transaction(pool, |tx| Box::pin(async move {
+            if let Some(exceeded) = Self::reserve_quota(tx, user_id, tier).await? {
+                return Ok(Err(exceeded));
+            }
+            
+            let project_row = sqlx::query!(
+                r#"
+                INSERT INTO projects (id, user_id, name, description, status, progress, technologies)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING id, user_id, name, description, status, progress, repository_url, technologies, is_template, visibility, created_at, updated_at
+                "#,
+                project_id,
+                user_id,
+                req.name,
+                req.description,
+                ProjectStatus::Pending.as_str(),
+                0i32,
+                &req.technologies
+            )
+            .fetch_one(&mut **tx)
+            .await?;
+            
+            Self::record_usage(tx, user_id, project_id).await?;
+            
+            Ok(Ok(Project {
+                id: project_row.id,
+                user_id: project_row.user_id,
+                name: project_row.name,
+                description: project_row.description,
+                status: ProjectStatus::Pending,
+                progress: project_row.progress,
+                repository_url: project_row.repository_url,
+                technologies: project_row.technologies,
+                is_template: project_row.is_template,
+                visibility: ProjectVisibility::Private,
+                created_at: project_row.created_at,
+                updated_at: project_row.updated_at,
+            }))
+        }))
+        .await
+    }
+    
+    // Locks the user row so concurrent creates for the same user are
+    // serialized between this check and the caller's insert. Usage comes from
+    // the append-only project_usage table, so deleted projects still count;
+    // projects that failed to generate don't.
+    async fn reserve_quota(
+        tx: &mut Transaction<'static, Postgres>,
+        user_id: Uuid,
+        tier: SubscriptionTier,
+    ) -> Result<Option<QuotaExceededResponse>> {
+        sqlx::query!("SELECT id FROM users WHERE id = $1 FOR UPDATE", user_id)
+            .fetch_one(&mut **tx)
+            .await?;
+        
+        let limit = match tier.monthly_project_limit() {
+            Some(limit) => limit,
+            None => return Ok(None),
+        };
+        
+        let usage = sqlx::query_scalar!(
             r#"
-            INSERT INTO projects (id, user_id, name, description, status, progress, technologies)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
-            RETURNING id, user_id, name, description, status, progress, repository_url, technologies, created_at, updated_at
+            SELECT COUNT(*) AS "count!"
+            FROM project_usage u
+            LEFT JOIN projects p ON p.id = u.project_id
+            WHERE u.user_id = $1
+              AND u.created_at >= date_trunc('month', NOW() AT TIME ZONE 'UTC') AT TIME ZONE 'UTC'
+              AND (p.status IS NULL OR p.status::text <> $2)
             "#,
-            project_id,
             user_id,
-            req.name,
-            req.description,
-            "pending",
-            0i32,
-            &req.technologies
+            ProjectStatus::Failed.as_str()
         )
-        .fetch_one(pool)
+        .fetch_one(&mut **tx)
         .await?;
         
-        Ok(Project {
-            id: project_row.id,
-            user_id: project_row.user_id,
-            name: project_row.name,
-            description: project_row.description,
-            status: ProjectStatus::Pending,
-            progress: project_row.progress,
-            repository_url: project_row.repository_url,
-            technologies: project_row.technologies,
-            created_at: project_row.created_at,
-            updated_at: project_row.updated_at,
-        })
+        if usage < limit {
+            return Ok(None);
+        }
+        
+        Ok(Some(QuotaExceededResponse {
+            error: "Monthly project quota exceeded".to_string(),
+            tier,
+            limit,
+            usage,
+        }))
     }
     
-    pub async fn list_projects(pool: &PgPool, user_id: Option<Uuid>) -> Result<Vec<Project>> {
-        let project_rows = if let Some(user_id) = user_id {
-            sqlx::query!(
-                r#"
-                SELECT id, user_id, name, description, status, progress, repository_url, technologies, created_at, updated_at
-                FROM projects 
-                WHERE user_id = $1
-                ORDER BY created_at DESC
-                "#,
-                user_id
-            )
-            .fetch_all(pool)
-            .await?
-        } else {
-            sqlx::query!(
-                r#"
-                SELECT id, user_id, name, description, status, progress, repository_url, technologies, created_at, updated_at
-                FROM projects 
-                ORDER BY created_at DESC
-                "#
-            )
-            .fetch_all(pool)
-            .await?
-        };
+    async fn record_usage(
+        tx: &mut Transaction<'static, Postgres>,
+        user_id: Uuid,
+        project_id: Uuid,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO project_usage (user_id, project_id) VALUES ($1, $2)",
+            user_id,
+            project_id
+        )
+        .execute(&mut **tx)
+        .await?;
+        
+        Ok(())
+    }
+    
+    // One page of matching projects plus the total size of the filtered set
+    pub async fn list_projects(
+        pool: &PgPool,
+        user_id: Option<Uuid>,
+        query: &ProjectListQuery,
+        status: Option<ProjectStatus>,
+    ) -> Result<(Vec<Project>, i64)> {
+        let status = status.map(|status| status.as_str());
+        
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM projects
+            WHERE deleted_at IS NULL
+              AND ($1::uuid IS NULL OR user_id = $1)
+              AND ($2::timestamptz IS NULL OR created_at >= $2)
+              AND ($3::timestamptz IS NULL OR created_at <= $3)
+              AND ($4::text IS NULL OR status::text = $4)
+            "#,
+            user_id,
+            query.created_after,
+            query.created_before,
+            status
+        )
+        .fetch_one(pool)
+        .await?;
+        
+        // Column and direction can't be bound as parameters, so each
+        // combination gets its own CASE; id keeps pages stable on ties
+        let project_rows = sqlx::query!(
+            r#"
+            SELECT id, user_id, name, description, status, progress, repository_url, technologies, is_template, visibility, created_at, updated_at
+            FROM projects 
+            WHERE deleted_at IS NULL
+              AND ($1::uuid IS NULL OR user_id = $1)
+              AND ($2::timestamptz IS NULL OR created_at >= $2)
+              AND ($3::timestamptz IS NULL OR created_at <= $3)
+              AND ($4::text IS NULL OR status::text = $4)
+            ORDER BY
+              CASE WHEN $5 = 'created_at' AND $6 = 'asc' THEN created_at END ASC,
+              CASE WHEN $5 = 'created_at' AND $6 = 'desc' THEN created_at END DESC,
+              CASE WHEN $5 = 'updated_at' AND $6 = 'asc' THEN updated_at END ASC,
+              CASE WHEN $5 = 'updated_at' AND $6 = 'desc' THEN updated_at END DESC,
+              id
+            LIMIT $7 OFFSET $8
+            "#,
+            user_id,
+            query.created_after,
+            query.created_before,
+            status,
+            query.sort.as_str(),
+            query.order.as_str(),
+            query.limit,
+            query.offset
+        )
+        .fetch_all(pool)
+        .await?;
         
         let projects = project_rows
             .into_iter()
-            .map(|row| Project {
+            .map(|row| Ok(Project {
                 id: row.id,
                 user_id: row.user_id,
                 name: row.name,
                 description: row.description,
-                status: match row.status.as_str() {
-                    "generating" => ProjectStatus::Generating,
-                    "complete" => ProjectStatus::Complete,
-                    "failed" => ProjectStatus::Failed,
-                    _ => ProjectStatus::Pending,
-                },
+                status: Self::parse_status(&row.status)?,
                 progress: row.progress,
                 repository_url: row.repository_url,
                 technologies: row.technologies,
+                is_template: row.is_template,
+                visibility: Self::parse_visibility(&row.visibility)?,
                 created_at: row.created_at,
                 updated_at: row.updated_at,
-            })
-            .collect();
+            }))
+            .collect::<Result<Vec<_>>>()?;
         
-        Ok(projects)
+        Ok((projects, total))
     }
     
-    pub async fn get_project(pool: &PgPool, project_id: Uuid) -> Result<Option<Project>> {
+    pub async fn get_project(
+        pool: &PgPool,
+        project_id: Uuid,
+        user_id: Option<Uuid>,
+    ) -> Result<Option<Project>> {
         let project_row = sqlx::query!(
             r#"
-            SELECT id, user_id, name, description, status, progress, repository_url, technologies, created_at, updated_at
+            SELECT id, user_id, name, description, status, progress, repository_url, technologies, is_template, visibility, created_at, updated_at
             FROM projects 
-            WHERE id = $1
+            WHERE id = $1 AND ($2::uuid IS NULL OR user_id = $2) AND deleted_at IS NULL
             "#,
-            project_id
+            project_id,
+            user_id
         )
         .fetch_optional(pool)
         .await?;
         
-        Ok(project_row.map(|row| Project {
-            id: row.id,
-            user_id: row.user_id,
-            name: row.name,
-            description: row.description,
-            status: match row.status.as_str() {
-                "generating" => ProjectStatus::Generating,
-                "complete" => ProjectStatus::Complete,
-                "failed" => ProjectStatus::Failed,
-                _ => ProjectStatus::Pending,
-            },
-            progress: row.progress,
-            repository_url: row.repository_url,
-            technologies: row.technologies,
-            created_at: row.created_at,
-            updated_at: row.updated_at,
-        }))
+        match project_row {
+            Some(row) => Ok(Some(Project {
+                id: row.id,
+                user_id: row.user_id,
+                name: row.name,
+                description: row.description,
+                status: Self::parse_status(&row.status)?,
+                progress: row.progress,
+                repository_url: row.repository_url,
+                technologies: row.technologies,
+                is_template: row.is_template,
+                visibility: Self::parse_visibility(&row.visibility)?,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            })),
+            None => Ok(None),
+        }
     }
     
-    pub async fn delete_project(pool: &PgPool, project_id: Uuid) -> Result<bool> {
-        let result = sqlx::query!(
-            "DELETE FROM projects WHERE id = $1",
-            project_id
+    // Publicly visible templates, newest first
+    pub async fn list_public_templates(pool: &PgPool, limit: i64, offset: i64) -> Result<Vec<Project>> {
+        let project_rows = sqlx::query!(
+            r#"
+            SELECT id, user_id, name, description, status, progress, repository_url, technologies, is_template, visibility, created_at, updated_at
+            FROM projects
+            WHERE is_template AND visibility = 'public' AND deleted_at IS NULL
+            ORDER BY created_at DESC, id
+            LIMIT $1 OFFSET $2
+            "#,
+            limit,
+            offset
         )
-        .execute(pool)
+        .fetch_all(pool)
         .await?;
         
-        Ok(result.rows_affected() > 0)
+        project_rows
+            .into_iter()
+            .map(|row| Ok(Project {
+                id: row.id,
+                user_id: row.user_id,
+                name: row.name,
+                description: row.description,
+                status: Self::parse_status(&row.status)?,
+                progress: row.progress,
+                repository_url: row.repository_url,
+                technologies: row.technologies,
+                is_template: row.is_template,
+                visibility: Self::parse_visibility(&row.visibility)?,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            }))
+            .collect()
+    }
+    
+    // Only the owner's row matches, so None means missing or not theirs
+    pub async fn update_template(
+        pool: &PgPool,
+        project_id: Uuid,
+        user_id: Uuid,
+        is_template: bool,
+        visibility: ProjectVisibility,
+    ) -> Result<Option<Project>> {
+        let project_row = sqlx::query!(
+            r#"
+            UPDATE projects
+            SET is_template = $3, visibility = $4, updated_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL
+            RETURNING id, user_id, name, description, status, progress, repository_url, technologies, is_template, visibility, created_at, updated_at
+            "#,
+            project_id,
+            user_id,
+            is_template,
+            visibility.as_str()
+        )
+        .fetch_optional(pool)
+        .await?;
+        
+        match project_row {
+            Some(row) => Ok(Some(Project {
+                id: row.id,
+                user_id: row.user_id,
+                name: row.name,
+                description: row.description,
+                status: Self::parse_status(&row.status)?,
+                progress: row.progress,
+                repository_url: row.repository_url,
+                technologies: row.technologies,
+                is_template: row.is_template,
+                visibility: Self::parse_visibility(&row.visibility)?,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            })),
+            None => Ok(None),
+        }
+    }
+    
+    // Copies a template's settings into a new pending project owned by
+    // `user`; artifacts are not copied. Private templates can only be cloned
+    // by their owner. Returns None when no such template is visible, and
+    // counts against the monthly quota like `create_project`.
+    pub async fn clone_template(
+        pool: &PgPool,
+        template_id: Uuid,
+        user: &User,
+    ) -> Result<Option<Result<Project, QuotaExceededResponse>>> {
+        let user_id = user.id;
+        let tier = user.subscription_tier;
+        let project_id = Uuid::new_v4();
+        
+        db::with_transaction(pool, |tx| Box::pin(async move {
+            // Look the template up first so a missing one is a 404 rather
+            // than a quota error; FOR SHARE keeps it from being deleted
+            // before the copy below
+            let template = sqlx::query!(
+                r#"
+                SELECT name, description, technologies
+                FROM projects
+                WHERE id = $1 AND is_template AND (visibility = 'public' OR user_id = $2)
+                  AND deleted_at IS NULL
+                FOR SHARE
+                "#,
+                template_id,
+                user_id
+            )
+            .fetch_optional(&mut **tx)
+            .await?;
+            
+            let template = match template {
+                Some(template) => template,
+                None => return Ok(None),
+            };
+            
+            if let Some(exceeded) = Self::reserve_quota(tx, user_id, tier).await? {
+                return Ok(Some(Err(exceeded)));
+            }
+            
+            let row = sqlx::query!(
+                r#"
+                INSERT INTO projects (id, user_id, name, description, status, progress, technologies)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING id, user_id, name, description, status, progress, repository_url, technologies, is_template, visibility, created_at, updated_at
+                "#,
+                project_id,
+                user_id,
+                template.name,
+                template.description,
+                ProjectStatus::Pending.as_str(),
+                0i32,
+                &template.technologies
+            )
+            .fetch_one(&mut **tx)
+            .await?;
+            
+            Self::record_usage(tx, user_id, project_id).await?;
+            
+            Ok(Some(Ok(Project {
+                id: row.id,
+                user_id: row.user_id,
+                name: row.name,
+                description: row.description,
+                status: ProjectStatus::Pending,
+                progress: row.progress,
+                repository_url: row.repository_url,
+                technologies: row.technologies,
+                is_template: row.is_template,
+                visibility: ProjectVisibility::Private,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            })))
+        }))
+        .await
+    }
+    
+    // Soft deletes by default; `hard` removes the row and its artifacts,
+    // including projects that were already soft deleted
+    pub async fn delete_project(
+        pool: &PgPool,
+        project_id: Uuid,
+        user_id: Option<Uuid>,
+        hard: bool,
+    ) -> Result<bool> {
+        let deleted = Self::bulk_delete_projects(pool, user_id, &[project_id], hard).await?;
+        Ok(!deleted.is_empty())
+    }
+    
+    // Deletes the given projects in one statement, returning the ids actually removed
+    pub async fn bulk_delete_projects(
+        pool: &PgPool,
+        user_id: Option<Uuid>,
+        project_ids: &[Uuid],
+        hard: bool,
+    ) -> Result<Vec<Uuid>> {
+        let deleted = if hard {
+            sqlx::query_scalar!(
+                r#"
+                DELETE FROM projects
+                WHERE id = ANY($1) AND ($2::uuid IS NULL OR user_id = $2)
+                RETURNING id
+                "#,
+                project_ids,
+                user_id
+            )
+            .fetch_all(pool)
+            .await?
+        } else {
+            sqlx::query_scalar!(
+                r#"
+                UPDATE projects
+                SET deleted_at = NOW(), updated_at = NOW()
+                WHERE id = ANY($1) AND ($2::uuid IS NULL OR user_id = $2) AND deleted_at IS NULL
+                RETURNING id
+                "#,
+                project_ids,
+                user_id
+            )
+            .fetch_all(pool)
+            .await?
+        };
+        
+        Ok(deleted)
     }
     
     pub async fn update_project_status(
@@ -141,12 +445,7 @@ impl ProjectService {
         status: ProjectStatus,
         progress: Option<i32>,
     ) -> Result<()> {
-        let status_str = match status {
-            ProjectStatus::Pending => "pending",
-            ProjectStatus::Generating => "generating",
-            ProjectStatus::Complete => "complete",
-            ProjectStatus::Failed => "failed",
-        };
+        let status_str = status.as_str();
         
         if let Some(progress) = progress {
             sqlx::query!(
@@ -177,4 +476,17 @@ impl ProjectService {
         
         Ok(())
     }
+    
+    // Unknown values mean the database and the enum have drifted apart
+    fn parse_status(value: &str) -> Result<ProjectStatus> {
+        ProjectStatus::try_from(value).inspect_err(|e| {
+            tracing::error!("Unexpected project status in database: {}", e);
+        })
+    }
+    
+    fn parse_visibility(value: &str) -> Result<ProjectVisibility> {
+        ProjectVisibility::try_from(value).inspect_err(|e| {
+            tracing::error!("Unexpected project visibility in database: {}", e);
+        })
+    }
 }
\ No newline at end of file
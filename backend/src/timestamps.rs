@@ -0,0 +1,10 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serializer;
+
+// All API timestamps go out as RFC 3339 with millisecond precision and a `Z` suffix
+pub fn serialize<S>(timestamp: &DateTime<Utc>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&timestamp.to_rfc3339_opts(SecondsFormat::Millis, true))
+}
\ No newline at end of file
@@ -19,7 +19,7 @@ const AuthPage: React.FC = () => {
   const loginMutation = useMutation({
     mutationFn: (credentials: LoginRequest) => authService.login(credentials),
     onSuccess: (data) => {
-      login(data.token, data.user)
+      login(data.token, data.refresh_token, data.user)
       toast.success('Successfully logged in!')
       navigate('/dashboard')
     },
@@ -1,6 +1,6 @@
-import axios from 'axios'
+import axios, { AxiosError, InternalAxiosRequestConfig } from 'axios'
 import { useAuthStore } from '../stores/authStore'
-import { LoginRequest, CreateUserRequest, LoginResponse, User } from '../types'
+import { LoginRequest, CreateUserRequest, LoginResponse, RefreshTokenResponse, User } from '../types'
 
 const api = axios.create({
   baseURL: '/api/v1',
@@ -15,14 +15,45 @@ api.interceptors.request.use((config) => {
   return config
 })
 
-// Response interceptor to handle auth errors
+// Shared so concurrent 401s wait on one refresh; refresh tokens are single-use
+let refreshRequest: Promise<string> | null = null
+
+async function refreshAccessToken(): Promise<string> {
+  const { refreshToken, setTokens } = useAuthStore.getState()
+  if (!refreshToken) {
+    throw new Error('No refresh token')
+  }
+
+  // Plain axios so a failed refresh doesn't re-enter this interceptor
+  const response = await axios.post<RefreshTokenResponse>('/api/v1/auth/refresh', {
+    refresh_token: refreshToken,
+  })
+  setTokens(response.data.token, response.data.refresh_token)
+  return response.data.token
+}
+
+// Response interceptor: on an expired access token, refresh once and retry
 api.interceptors.response.use(
   (response) => response,
-  (error) => {
-    if (error.response?.status === 401) {
+  async (error: AxiosError) => {
+    const request = error.config as (InternalAxiosRequestConfig & { _retried?: boolean }) | undefined
+
+    if (error.response?.status !== 401 || !request || request._retried) {
+      return Promise.reject(error)
+    }
+
+    request._retried = true
+    try {
+      refreshRequest ??= refreshAccessToken().finally(() => {
+        refreshRequest = null
+      })
+      const token = await refreshRequest
+      request.headers.Authorization = `Bearer ${token}`
+      return api(request)
+    } catch {
       useAuthStore.getState().logout()
+      return Promise.reject(error)
     }
-    return Promise.reject(error)
   }
 )
 
@@ -1,5 +1,5 @@
 import api from './api'
-import { Project, CreateProjectRequest } from '../types'
+import { Project, CreateProjectRequest, ProjectListResponse } from '../types'
 
 export const projectService = {
   async createProject(projectData: CreateProjectRequest): Promise<Project> {
@@ -8,8 +8,8 @@ export const projectService = {
   },
 
   async getProjects(): Promise<Project[]> {
-    const response = await api.get('/projects')
-    return response.data
+    const response = await api.get<ProjectListResponse>('/projects')
+    return response.data.projects
   },
 
   async getProject(id: string): Promise<Project> {
@@ -5,8 +5,10 @@ import { User } from '../types'
 interface AuthState {
   user: User | null
   token: string | null
+  refreshToken: string | null
   isAuthenticated: boolean
-  login: (token: string, user: User) => void
+  login: (token: string, refreshToken: string, user: User) => void
+  setTokens: (token: string, refreshToken: string) => void
   logout: () => void
 }
 
@@ -15,17 +17,25 @@ export const useAuthStore = create<AuthState>()(
     (set) => ({
       user: null,
       token: null,
+      refreshToken: null,
       isAuthenticated: false,
-      login: (token: string, user: User) =>
+      login: (token: string, refreshToken: string, user: User) =>
         set({
           user,
           token,
+          refreshToken,
           isAuthenticated: true,
         }),
+      setTokens: (token: string, refreshToken: string) =>
+        set({
+          token,
+          refreshToken,
+        }),
       logout: () =>
         set({
           user: null,
           token: null,
+          refreshToken: null,
           isAuthenticated: false,
         }),
     }),
@@ -17,6 +17,13 @@ export interface Project {
   updated_at: string
 }
 
+export interface ProjectListResponse {
+  projects: Project[]
+  total: number
+  limit: number
+  offset: number
+}
+
 export interface CreateProjectRequest {
   name: string
   description: string
@@ -36,5 +43,11 @@ export interface CreateUserRequest {
 
 export interface LoginResponse {
   token: string
+  refresh_token: string
   user: User
+}
+
+export interface RefreshTokenResponse {
+  token: string
+  refresh_token: string
 }
\ No newline at end of file
@@ -0,0 +1,17 @@
+-- Track successive versions of each artifact type per project
+ALTER TABLE generation_artifacts ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+-- Existing duplicates become versions 1..n in creation order
+UPDATE generation_artifacts
+SET version = numbered.rn
+FROM (
+    SELECT id, row_number() OVER (
+        PARTITION BY project_id, artifact_type
+        ORDER BY created_at, id
+    ) AS rn
+    FROM generation_artifacts
+) AS numbered
+WHERE generation_artifacts.id = numbered.id;
+
+CREATE UNIQUE INDEX idx_generation_artifacts_version
+    ON generation_artifacts(project_id, artifact_type, version);
\ No newline at end of file
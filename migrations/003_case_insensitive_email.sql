@@ -0,0 +1,7 @@
+-- Emails are stored trimmed and lowercased by the application; enforce
+-- uniqueness case-insensitively so differently-cased duplicates are rejected.
+-- Fails if such duplicates already exist, which must be merged by hand first.
+UPDATE users SET email = lower(trim(email)) WHERE email <> lower(trim(email));
+
+DROP INDEX IF EXISTS idx_users_email;
+CREATE UNIQUE INDEX idx_users_email_lower ON users (lower(email));
@@ -0,0 +1,10 @@
+-- Projects can be published as templates that other users clone into their
+-- own accounts. Only public templates are listed to everyone.
+CREATE TYPE project_visibility AS ENUM ('private', 'public');
+
+ALTER TABLE projects
+    ADD COLUMN is_template BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN visibility project_visibility NOT NULL DEFAULT 'private';
+
+CREATE INDEX idx_projects_public_templates ON projects(created_at DESC)
+    WHERE is_template AND visibility = 'public';
\ No newline at end of file
@@ -0,0 +1,13 @@
+-- Append-only record of every project a user starts. Rows outlive the project
+-- itself so that deleting projects does not hand monthly quota back.
+CREATE TABLE project_usage (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    project_id UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_project_usage_user_created ON project_usage(user_id, created_at);
+
+INSERT INTO project_usage (user_id, project_id, created_at)
+SELECT user_id, id, created_at FROM projects;
\ No newline at end of file
@@ -0,0 +1,6 @@
+-- Deleted projects are kept with a deletion timestamp unless a hard delete is
+-- requested; every read path filters them out.
+ALTER TABLE projects ADD COLUMN deleted_at TIMESTAMPTZ;
+
+CREATE INDEX idx_projects_user_live ON projects(user_id, created_at DESC)
+    WHERE deleted_at IS NULL;
\ No newline at end of file